@@ -0,0 +1,247 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitStr};
+
+#[derive(Debug)]
+enum Segment {
+    Literal(String),
+    /// A placeholder, `None` for `{_}` which matches anything and is discarded.
+    Field(Option<String>),
+}
+
+fn parse_template(template: &str) -> Result<Vec<Segment>, String> {
+    let mut segments = vec![];
+    let mut literal = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                literal.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                literal.push('}');
+            }
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => {
+                            return Err(format!("unterminated placeholder {{{name}"));
+                        }
+                    }
+                }
+                let name = name.trim();
+                if name.is_empty() {
+                    return Err("empty placeholder, use {_} to skip text".to_string());
+                }
+                if !literal.is_empty() {
+                    segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                } else if matches!(segments.last(), Some(Segment::Field(_))) {
+                    return Err(format!(
+                        "placeholder {{{name}}} directly follows another placeholder"
+                    ));
+                }
+                segments.push(Segment::Field((name != "_").then(|| name.to_string())));
+            }
+            '}' => {
+                return Err("unmatched }, use }} for a literal brace".to_string());
+            }
+            c => literal.push(c),
+        }
+    }
+    if !literal.is_empty() {
+        segments.push(Segment::Literal(literal));
+    }
+    Ok(segments)
+}
+
+/// Derives `FromStr` from a sentence template given in a `#[template("...")]` attribute.
+///
+/// Every named field has to appear exactly once as a `{field}` placeholder and is parsed with its
+/// own `FromStr` impl, `{_}` skips over text. A placeholder extends up to the first occurrence of
+/// the literal text following it. Errors are reported as `crate::common::TemplateError`.
+#[proc_macro_derive(FromTemplate, attributes(template))]
+pub fn derive_from_template(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(s) => match &s.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new(
+                    input.span(),
+                    "FromTemplate requires named fields",
+                ));
+            }
+        },
+        _ => {
+            return Err(syn::Error::new(
+                input.span(),
+                "FromTemplate can only be derived for structs",
+            ));
+        }
+    };
+
+    let attr = input
+        .attrs
+        .iter()
+        .find(|a| a.path().is_ident("template"))
+        .ok_or_else(|| syn::Error::new(input.span(), "missing #[template(\"...\")] attribute"))?;
+    let template: LitStr = attr.parse_args()?;
+    let segments =
+        parse_template(&template.value()).map_err(|e| syn::Error::new(template.span(), e))?;
+
+    let mut used = vec![];
+    for s in &segments {
+        if let Segment::Field(Some(name)) = s {
+            if !fields.iter().any(|f| f.ident.as_ref().unwrap() == name) {
+                return Err(syn::Error::new(
+                    template.span(),
+                    format!("no field named `{name}`"),
+                ));
+            }
+            if used.contains(name) {
+                return Err(syn::Error::new(
+                    template.span(),
+                    format!("field `{name}` appears more than once"),
+                ));
+            }
+            used.push(name.clone());
+        }
+    }
+    if let Some(f) = fields
+        .iter()
+        .find(|f| !used.contains(&f.ident.as_ref().unwrap().to_string()))
+    {
+        return Err(syn::Error::new(
+            f.span(),
+            "field does not appear in the template",
+        ));
+    }
+
+    let mut steps = vec![];
+    let mut i = 0;
+    while i < segments.len() {
+        match &segments[i] {
+            Segment::Literal(literal) => {
+                steps.push(quote! {
+                    rest = rest.strip_prefix(#literal).ok_or(
+                        crate::common::TemplateError::MissingLiteral {
+                            expected: #literal,
+                            position: input.len() - rest.len(),
+                        },
+                    )?;
+                });
+                i += 1;
+            }
+            Segment::Field(name) => {
+                let take = match segments.get(i + 1) {
+                    Some(Segment::Literal(next)) => {
+                        i += 2;
+                        quote! {
+                            let (text, after) = rest.split_once(#next).ok_or(
+                                crate::common::TemplateError::MissingLiteral {
+                                    expected: #next,
+                                    position: input.len() - rest.len(),
+                                },
+                            )?;
+                            rest = after;
+                        }
+                    }
+                    _ => {
+                        i += 1;
+                        quote! {
+                            let text = rest;
+                            rest = "";
+                        }
+                    }
+                };
+                let parse = match name {
+                    Some(name) => {
+                        let field = fields
+                            .iter()
+                            .find(|f| f.ident.as_ref().unwrap() == name)
+                            .unwrap();
+                        let ident = field.ident.as_ref().unwrap();
+                        let ty = &field.ty;
+                        let var = format_ident!("field_{}", ident);
+                        quote! {
+                            let #var = text.parse::<#ty>().map_err(|e| {
+                                crate::common::TemplateError::InvalidField {
+                                    field: #name,
+                                    text: text.to_string(),
+                                    reason: e.to_string(),
+                                }
+                            })?;
+                        }
+                    }
+                    None => quote! {
+                        let _ = text;
+                    },
+                };
+                steps.push(quote! {
+                    #take
+                    #parse
+                });
+            }
+        }
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let inits = fields.iter().map(|f| {
+        let ident = f.ident.as_ref().unwrap();
+        let var = format_ident!("field_{}", ident);
+        quote! { #ident: #var }
+    });
+    Ok(quote! {
+        impl #impl_generics ::std::str::FromStr for #name #ty_generics #where_clause {
+            type Err = crate::common::TemplateError;
+
+            fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+                let input = s.trim();
+                let mut rest = input;
+                #(#steps)*
+                if !rest.is_empty() {
+                    return Err(crate::common::TemplateError::TrailingInput {
+                        position: input.len() - rest.len(),
+                    });
+                }
+                Ok(Self { #(#inits),* })
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_template_errors() {
+        assert_eq!(
+            parse_template("{name} is {age").unwrap_err(),
+            "unterminated placeholder {age"
+        );
+        assert_eq!(
+            parse_template("{}").unwrap_err(),
+            "empty placeholder, use {_} to skip text"
+        );
+        assert_eq!(
+            parse_template("a}").unwrap_err(),
+            "unmatched }, use }} for a literal brace"
+        );
+        assert_eq!(parse_template("{{{a}}}").unwrap().len(), 3);
+    }
+}
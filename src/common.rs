@@ -1,13 +1,39 @@
-use nalgebra::{Dim, Matrix, Matrix2, Scalar, Storage, Vector2};
-use nalgebra::{SVector, Vector3};
+use nalgebra::{DMatrix, DVector, SVector, Vector3};
+use nalgebra::{Matrix2, Scalar, Vector2};
 use num::rational::Ratio;
-use num::{Rational64, Signed};
+use num::Rational64;
 use std::fmt::Debug;
 use std::hash::Hash;
-use std::ops::{Index, IndexMut};
+use std::ops::{
+    Add, AddAssign, Index, IndexMut, Mul, MulAssign, Range, RangeInclusive, Sub, SubAssign,
+};
 use std::str::FromStr;
 use thiserror::Error;
 
+mod bitgrid;
+mod bitset;
+pub mod brackets;
+pub mod combinatorics;
+mod cycle;
+mod directions;
+pub mod geometry;
+mod grid3;
+mod grid_search;
+mod norms;
+pub mod number_theory;
+mod range_set;
+
+pub use bitgrid::{BitGrid, ParseBitGridError};
+pub use bitset::{BitSet, BitStorage, FixedBitSet, HeapBitSet, KSubsets, Submasks};
+pub use cycle::{brent, floyd, Cycle, StateHistory};
+pub use directions::{axial_to_cube, cube_to_axial, hex_distance, Direction8, HexDirection};
+pub use grid3::{Axis, Grid3};
+pub use norms::{
+    chebyshev_distance, induced_l1_norm, induced_linf_norm, linf_norm, lp1_norm, lp_norm,
+    lp_norm_pow, manhattan_distance, squared_euclidean_distance, squared_l2_norm, NormError,
+};
+pub use range_set::{union_area, RangeSet, Rect, RectSet};
+
 pub type Rational128 = Ratio<i128>;
 pub type Vec2i = Vector2<i64>;
 pub type Vec2r = Vector2<Rational64>;
@@ -20,18 +46,11 @@ pub type Mat2i = Matrix2<i64>;
 pub type Mat2r = Matrix2<Rational64>;
 pub type Mat2r128 = Matrix2<Rational128>;
 
-pub fn lp1_norm<T: Scalar + Signed, R: Dim, C: Dim, S: Storage<T, R, C>>(
-    v: &Matrix<T, R, C, S>,
-) -> T {
-    match v.shape() {
-        (1, _) | (_, 1) => v.fold(T::zero(), |a, e| a + e.abs()),
-        _ => {
-            panic!("lp1 norm is only implemented for vectors")
-        }
-    }
-}
-
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde-serialize",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub enum Direction {
     North,
     South,
@@ -55,6 +74,35 @@ impl TryFrom<char> for Direction {
     }
 }
 
+#[derive(Error, Debug, Clone, Eq, PartialEq)]
+#[error("unknown direction {0:?}")]
+pub struct ParseDirectionError(pub String);
+
+/// Lowercases `s` and strips separators, so that e.g. `North-East` and `northeast` compare equal.
+fn normalize_direction(s: &str) -> String {
+    s.trim()
+        .chars()
+        .filter(|c| !matches!(c, '-' | '_' | ' '))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+impl FromStr for Direction {
+    type Err = ParseDirectionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match normalize_direction(s).as_str() {
+            "n" | "north" | "u" | "up" | "^" => Self::North,
+            "s" | "south" | "d" | "down" | "v" => Self::South,
+            "e" | "east" | "r" | "right" | ">" => Self::East,
+            "w" | "west" | "l" | "left" | "<" => Self::West,
+            _ => {
+                return Err(ParseDirectionError(s.to_string()));
+            }
+        })
+    }
+}
+
 impl Direction {
     pub const VALUES: [Self; 4] = [Self::North, Self::East, Self::South, Self::West];
 
@@ -85,6 +133,12 @@ impl Direction {
         }
     }
 
+    /// Rotates clockwise by `steps` quarter turns, negative values rotate counter-clockwise.
+    pub fn rotate(&self, steps: i64) -> Direction {
+        let index = Self::VALUES.iter().position(|d| d == self).unwrap() as i64;
+        Self::VALUES[(index + steps).rem_euclid(Self::VALUES.len() as i64) as usize]
+    }
+
     pub fn vec(&self) -> Vec2i {
         match self {
             Self::North => Vec2i::new(0, -1),
@@ -104,12 +158,48 @@ impl Direction {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde-serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "RawGrid<T>")
+)]
 pub struct Grid<T> {
     pub size_x: usize,
     pub size_y: usize,
     grid: Vec<T>,
 }
 
+/// The unchecked shape of a serialized [`Grid`].
+#[cfg(feature = "serde-serialize")]
+#[derive(serde::Deserialize)]
+struct RawGrid<T> {
+    size_x: usize,
+    size_y: usize,
+    grid: Vec<T>,
+}
+
+#[cfg(feature = "serde-serialize")]
+impl<T> TryFrom<RawGrid<T>> for Grid<T> {
+    type Error = String;
+
+    fn try_from(raw: RawGrid<T>) -> Result<Self, Self::Error> {
+        if raw.grid.len() != raw.size_x * raw.size_y {
+            return Err(format!(
+                "expected {} cells for a {}x{} grid, got {}",
+                raw.size_x * raw.size_y,
+                raw.size_x,
+                raw.size_y,
+                raw.grid.len()
+            ));
+        }
+        Ok(Self {
+            size_x: raw.size_x,
+            size_y: raw.size_y,
+            grid: raw.grid,
+        })
+    }
+}
+
 impl<T> FromStr for Grid<T>
 where
     char: TryInto<T>,
@@ -164,6 +254,65 @@ impl<T> Grid<T> {
         pos.x >= 0 && (pos.x as usize) < self.size_x && pos.y >= 0 && (pos.y as usize) < self.size_y
     }
 
+    fn index_of(&self, pos: &Vec2i) -> Option<usize> {
+        self.in_bounds(pos)
+            .then(|| (pos.x as usize) + self.size_x * (pos.y as usize))
+    }
+
+    fn out_of_bounds(&self, pos: &Vec2i) -> ! {
+        panic!(
+            "position ({}, {}) is out of bounds for a {}x{} grid",
+            pos.x, pos.y, self.size_x, self.size_y
+        )
+    }
+
+    fn assert_same_size<U>(&self, other: &Grid<U>) {
+        assert!(
+            self.size_x == other.size_x && self.size_y == other.size_y,
+            "grids of different sizes: {}x{} and {}x{}",
+            self.size_x,
+            self.size_y,
+            other.size_x,
+            other.size_y
+        );
+    }
+
+    pub fn get(&self, pos: &Vec2i) -> Option<&T> {
+        self.index_of(pos).map(|i| &self.grid[i])
+    }
+
+    pub fn get_mut(&mut self, pos: &Vec2i) -> Option<&mut T> {
+        self.index_of(pos).map(|i| &mut self.grid[i])
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            size_x: self.size_x,
+            size_y: self.size_y,
+            grid: self.grid.iter().map(f).collect(),
+        }
+    }
+
+    /// Combines the cells at the same positions, panics if the grids have different sizes.
+    pub fn zip_with<U, V>(&self, other: &Grid<U>, mut f: impl FnMut(&T, &U) -> V) -> Grid<V> {
+        self.assert_same_size(other);
+        Grid {
+            size_x: self.size_x,
+            size_y: self.size_y,
+            grid: self
+                .grid
+                .iter()
+                .zip(&other.grid)
+                .map(|(a, b)| f(a, b))
+                .collect(),
+        }
+    }
+
+    /// Folds over all cells in row-major order together with their positions.
+    pub fn fold<B>(&self, init: B, mut f: impl FnMut(B, Vec2i, &T) -> B) -> B {
+        self.pos_iter().fold(init, |acc, (pos, t)| f(acc, pos, t))
+    }
+
     pub fn mod_get(&self, pos: &Vec2i) -> &T {
         let x = pos.x.rem_euclid(self.size_x as i64);
         let y = pos.y.rem_euclid(self.size_y as i64);
@@ -210,16 +359,47 @@ impl<T> Index<Vec2i> for Grid<T> {
     type Output = T;
 
     fn index(&self, index: Vec2i) -> &Self::Output {
-        &self.grid[(index.x as usize) + self.size_x * (index.y as usize)]
+        match self.index_of(&index) {
+            Some(i) => &self.grid[i],
+            None => self.out_of_bounds(&index),
+        }
     }
 }
 
 impl<T> IndexMut<Vec2i> for Grid<T> {
     fn index_mut(&mut self, index: Vec2i) -> &mut Self::Output {
-        &mut self.grid[(index.x as usize) + self.size_x * (index.y as usize)]
+        match self.index_of(&index) {
+            Some(i) => &mut self.grid[i],
+            None => self.out_of_bounds(&index),
+        }
     }
 }
 
+macro_rules! impl_grid_op {
+    ($op:ident, $fn:ident, $op_assign:ident, $fn_assign:ident) => {
+        impl<T: Clone + $op<Output = T>> $op<&Grid<T>> for &Grid<T> {
+            type Output = Grid<T>;
+
+            fn $fn(self, rhs: &Grid<T>) -> Grid<T> {
+                self.zip_with(rhs, |a, b| a.clone().$fn(b.clone()))
+            }
+        }
+
+        impl<T: Clone + $op_assign> $op_assign<&Grid<T>> for Grid<T> {
+            fn $fn_assign(&mut self, rhs: &Grid<T>) {
+                self.assert_same_size(rhs);
+                for (a, b) in self.grid.iter_mut().zip(&rhs.grid) {
+                    a.$fn_assign(b.clone());
+                }
+            }
+        }
+    };
+}
+
+impl_grid_op!(Add, add, AddAssign, add_assign);
+impl_grid_op!(Sub, sub, SubAssign, sub_assign);
+impl_grid_op!(Mul, mul, MulAssign, mul_assign);
+
 pub fn parse_split_whitespace<T: FromStr, B: FromIterator<T>>(
     s: &str,
 ) -> Result<B, <T as FromStr>::Err> {
@@ -245,6 +425,104 @@ pub fn parse_lines<T: FromStr, B: FromIterator<T>>(s: &str) -> Result<B, <T as F
         .collect::<Result<_, _>>()
 }
 
+/// A parse error together with where in the input it happened.
+#[derive(Error, Debug)]
+#[error(
+    "line {line}{}: cannot parse {text:?}",
+    field.map(|f| format!(", field {f}")).unwrap_or_default()
+)]
+pub struct ParseContextError<E> {
+    /// 1-based line number.
+    pub line: usize,
+    /// 1-based field number within the input, only set when parsing separated fields.
+    pub field: Option<usize>,
+    /// The raw line for [`parse_lines_with_context`], the field otherwise.
+    pub text: String,
+    /// Byte range of `text` in the input.
+    pub span: Range<usize>,
+    #[source]
+    pub source: E,
+}
+
+/// Byte range of `sub` within `s`, `sub` has to be a subslice of `s`.
+fn span_in(s: &str, sub: &str) -> Range<usize> {
+    let start = sub.as_ptr() as usize - s.as_ptr() as usize;
+    start..start + sub.len()
+}
+
+fn parse_fields_with_context<'a, T: FromStr, B: FromIterator<T>>(
+    s: &str,
+    fields: impl Iterator<Item = &'a str>,
+) -> Result<B, ParseContextError<<T as FromStr>::Err>> {
+    fields
+        .enumerate()
+        .map(|(i, field)| {
+            field.parse().map_err(|source| {
+                let span = span_in(s, field);
+                ParseContextError {
+                    line: s[..span.start].matches('\n').count() + 1,
+                    field: Some(i + 1),
+                    text: field.to_string(),
+                    span,
+                    source,
+                }
+            })
+        })
+        .collect()
+}
+
+/// Like [`parse_split_whitespace`], but reports which field failed.
+pub fn parse_split_whitespace_with_context<T: FromStr, B: FromIterator<T>>(
+    s: &str,
+) -> Result<B, ParseContextError<<T as FromStr>::Err>> {
+    parse_fields_with_context(s, s.split_whitespace())
+}
+
+/// Like [`parse_split`], but reports which field failed.
+pub fn parse_split_with_context<T: FromStr, B: FromIterator<T>>(
+    s: &str,
+    pat: char,
+) -> Result<B, ParseContextError<<T as FromStr>::Err>> {
+    parse_fields_with_context(s, s.split(pat).map(str::trim).filter(|s| !s.is_empty()))
+}
+
+/// Like [`parse_lines`], but reports which line failed.
+pub fn parse_lines_with_context<T: FromStr, B: FromIterator<T>>(
+    s: &str,
+) -> Result<B, ParseContextError<<T as FromStr>::Err>> {
+    s.lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(i, l)| {
+            l.trim().parse().map_err(|source| ParseContextError {
+                line: i + 1,
+                field: None,
+                text: l.to_string(),
+                span: span_in(s, l),
+                source,
+            })
+        })
+        .collect()
+}
+
+/// The error returned by `FromStr` impls generated with `#[derive(FromTemplate)]`.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    #[error("expected {expected:?} at byte {position}")]
+    MissingLiteral {
+        expected: &'static str,
+        position: usize,
+    },
+    #[error("invalid value {text:?} for field `{field}`: {reason}")]
+    InvalidField {
+        field: &'static str,
+        text: String,
+        reason: String,
+    },
+    #[error("unexpected trailing input at byte {position}")]
+    TrailingInput { position: usize },
+}
+
 #[derive(Error, Debug)]
 pub enum ParseVecError<T> {
     #[error("missing element")]
@@ -255,17 +533,34 @@ pub enum ParseVecError<T> {
     ParseError(#[from] T),
 }
 
+/// The separators [`parse_vec`] and [`parse_dvec`] split on.
+pub fn is_default_separator(c: char) -> bool {
+    matches!(c, ',' | ';' | '|') || c.is_whitespace()
+}
+
+fn split_vec_elements<'a>(
+    s: &'a str,
+    is_separator: impl Fn(char) -> bool + 'a,
+) -> impl Iterator<Item = &'a str> + 'a {
+    s.trim_matches(|c: char| {
+        matches!(c, '(' | ')' | '[' | ']' | '{' | '}' | '|') || c.is_whitespace()
+    })
+    .split(is_separator)
+    .map(str::trim)
+    .filter(|s| !s.is_empty())
+}
+
 pub fn parse_vec<T: Scalar + FromStr, const D: usize>(
     s: &str,
 ) -> Result<SVector<T, D>, ParseVecError<<T as FromStr>::Err>> {
-    let mut it = s
-        .trim_matches(|c: char| {
-            matches!(c, '(' | ')' | '[' | ']' | '{' | '}' | '|') || c.is_whitespace()
-        })
-        .split(|c: char| matches!(c, ',' | ';' | '|') || c.is_whitespace())
-        .map(str::trim)
-        .filter(|s| !s.is_empty())
-        .map(str::parse);
+    parse_vec_with(s, is_default_separator)
+}
+
+pub fn parse_vec_with<T: Scalar + FromStr, const D: usize>(
+    s: &str,
+    is_separator: impl Fn(char) -> bool,
+) -> Result<SVector<T, D>, ParseVecError<<T as FromStr>::Err>> {
+    let mut it = split_vec_elements(s, is_separator).map(str::parse);
 
     let mut data: [Option<T>; D] = std::array::from_fn(|_| None);
     for elem in data.iter_mut() {
@@ -278,3 +573,267 @@ pub fn parse_vec<T: Scalar + FromStr, const D: usize>(
 
     Ok(SVector::from_iterator(data.into_iter().flatten()))
 }
+
+pub fn parse_dvec<T: Scalar + FromStr>(s: &str) -> Result<DVector<T>, <T as FromStr>::Err> {
+    parse_dvec_with(s, is_default_separator)
+}
+
+pub fn parse_dvec_with<T: Scalar + FromStr>(
+    s: &str,
+    is_separator: impl Fn(char) -> bool,
+) -> Result<DVector<T>, <T as FromStr>::Err> {
+    let data: Vec<T> = split_vec_elements(s, is_separator)
+        .map(str::parse)
+        .collect::<Result<_, _>>()?;
+    Ok(DVector::from_vec(data))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ParsedRange<T> {
+    Exclusive(Range<T>),
+    Inclusive(RangeInclusive<T>),
+}
+
+#[derive(Error, Debug)]
+pub enum ParseRangeError<T> {
+    #[error("missing .. or ..=")]
+    MissingSeparator,
+    #[error("parse error")]
+    ParseError(#[from] T),
+}
+
+/// Parses `a..b` or `a..=b`.
+pub fn parse_range<T: FromStr>(
+    s: &str,
+) -> Result<ParsedRange<T>, ParseRangeError<<T as FromStr>::Err>> {
+    let (start, end) = s
+        .split_once("..")
+        .ok_or(ParseRangeError::MissingSeparator)?;
+    let start = start.trim().parse()?;
+    Ok(match end.strip_prefix('=') {
+        Some(end) => ParsedRange::Inclusive(start..=end.trim().parse()?),
+        None => ParsedRange::Exclusive(start..end.trim().parse()?),
+    })
+}
+
+#[derive(Error, Debug)]
+pub enum ParseMatrixError<T> {
+    #[error("row {row} has {found} elements, expected {expected}")]
+    RaggedRow {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    #[error("parse error")]
+    ParseError(#[from] T),
+}
+
+/// Parses a block with one matrix row per non-empty line, elements are separated by commas or
+/// whitespace.
+pub fn parse_matrix<T: Scalar + FromStr>(
+    s: &str,
+) -> Result<DMatrix<T>, ParseMatrixError<<T as FromStr>::Err>> {
+    let mut ncols = None;
+    let mut nrows = 0;
+    let mut data = vec![];
+    for line in s.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let row = parse_dvec_with::<T>(line, |c| c == ',' || c.is_whitespace())?;
+        match ncols {
+            None => ncols = Some(row.len()),
+            Some(expected) if expected != row.len() => {
+                return Err(ParseMatrixError::RaggedRow {
+                    row: nrows + 1,
+                    expected,
+                    found: row.len(),
+                });
+            }
+            _ => {}
+        }
+        nrows += 1;
+        data.extend(row.iter().cloned());
+    }
+
+    Ok(DMatrix::from_row_iterator(nrows, ncols.unwrap_or(0), data))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_lines_with_context() {
+        let input = "1\n  \n  2\nx3\n4";
+        let e = parse_lines_with_context::<i64, Vec<_>>(input).unwrap_err();
+        assert_eq!(e.line, 4);
+        assert_eq!(e.field, None);
+        assert_eq!(e.text, "x3");
+        assert_eq!(&input[e.span.clone()], "x3");
+        assert_eq!(e.to_string(), "line 4: cannot parse \"x3\"");
+        assert_eq!(
+            parse_lines_with_context::<i64, Vec<_>>("1\n  2\n").unwrap(),
+            vec![1, 2]
+        );
+    }
+
+    #[test]
+    fn test_parse_split_with_context() {
+        let input = "1, 2,\n3, y, 5";
+        let e = parse_split_with_context::<i64, Vec<_>>(input, ',').unwrap_err();
+        assert_eq!((e.line, e.field), (2, Some(4)));
+        assert_eq!(&input[e.span.clone()], "y");
+        assert_eq!(e.to_string(), "line 2, field 4: cannot parse \"y\"");
+        let e = parse_split_whitespace_with_context::<u8, Vec<_>>("1 2 300").unwrap_err();
+        assert_eq!((e.line, e.field, e.span.clone()), (1, Some(3), 4..7));
+        assert!(anyhow::Error::from(e)
+            .chain()
+            .any(|e| e.to_string().contains("too large")));
+    }
+
+    #[derive(Debug, PartialEq, aoc2015_derive::FromTemplate)]
+    #[template("{name} is {age} years old{_}")]
+    struct Person {
+        name: String,
+        age: u8,
+    }
+
+    #[test]
+    fn test_from_template() {
+        assert_eq!(
+            "Alice is 42 years old!".parse(),
+            Ok(Person {
+                name: "Alice".to_string(),
+                age: 42
+            })
+        );
+        assert_eq!(
+            "Bob is 300 years old".parse::<Person>(),
+            Err(TemplateError::InvalidField {
+                field: "age",
+                text: "300".to_string(),
+                reason: "number too large to fit in target type".to_string()
+            })
+        );
+        assert_eq!(
+            "Bob was 30 years old".parse::<Person>(),
+            Err(TemplateError::MissingLiteral {
+                expected: " is ",
+                position: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_vec() {
+        assert_eq!(parse_vec::<i64, 2>("(1, -2)").unwrap(), Vec2i::new(1, -2));
+        assert_eq!(
+            parse_vec_with::<i64, 3>("1x2x3", |c| c == 'x').unwrap(),
+            Vec3i::new(1, 2, 3)
+        );
+        assert!(matches!(
+            parse_vec::<i64, 3>("1,2"),
+            Err(ParseVecError::MissingElement)
+        ));
+        assert_eq!(
+            parse_dvec::<u8>("[1; 2; 3; 4]").unwrap(),
+            DVector::from_vec(vec![1, 2, 3, 4])
+        );
+        assert_eq!(parse_dvec::<u8>("").unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("3..7").unwrap(), ParsedRange::Exclusive(3..7));
+        assert_eq!(
+            parse_range("-3 ..= 7").unwrap(),
+            ParsedRange::Inclusive(-3..=7)
+        );
+        assert!(matches!(
+            parse_range::<i64>("3-7"),
+            Err(ParseRangeError::MissingSeparator)
+        ));
+        assert!(matches!(
+            parse_range::<i64>("3..x"),
+            Err(ParseRangeError::ParseError(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_matrix() {
+        let m = parse_matrix::<i64>("0 464 518\n464, 0, 141\n\n518 141 0\n").unwrap();
+        assert_eq!(m.shape(), (3, 3));
+        assert_eq!(m[(1, 2)], 141);
+        assert_eq!(m, m.transpose());
+        assert!(matches!(
+            parse_matrix::<i64>("1 2\n3"),
+            Err(ParseMatrixError::RaggedRow {
+                row: 2,
+                expected: 2,
+                found: 1
+            })
+        ));
+    }
+
+    #[cfg(feature = "serde-serialize")]
+    #[test]
+    fn test_serde_roundtrip() {
+        let grid: Grid<char> = "ab\ncd\n"
+            .parse()
+            .unwrap_or_else(|e: std::convert::Infallible| match e {});
+        let json = serde_json::to_string(&grid).unwrap();
+        assert_eq!(serde_json::from_str::<Grid<char>>(&json).unwrap(), grid);
+        assert!(
+            serde_json::from_str::<Grid<char>>(r#"{"size_x":2,"size_y":2,"grid":["a"]}"#).is_err()
+        );
+
+        let dir: Direction =
+            serde_json::from_str(&serde_json::to_string(&Direction::West).unwrap()).unwrap();
+        assert_eq!(dir, Direction::West);
+        let v = Vec2r::new(Rational64::new(1, 2), Rational64::new(-3, 1));
+        assert_eq!(
+            serde_json::from_str::<Vec2r>(&serde_json::to_string(&v).unwrap()).unwrap(),
+            v
+        );
+    }
+
+    #[test]
+    fn test_grid_checked_access() {
+        let mut grid: Grid<char> = "ab\ncd\n".parse().unwrap();
+        assert_eq!(grid.get(&Vec2i::new(1, 1)), Some(&'d'));
+        assert_eq!(grid.get(&Vec2i::new(-1, 0)), None);
+        // would alias (0, 1) in the flat storage
+        assert_eq!(grid.get(&Vec2i::new(2, 0)), None);
+        *grid.get_mut(&Vec2i::new(0, 1)).unwrap() = 'x';
+        assert_eq!(grid[Vec2i::new(0, 1)], 'x');
+        assert!(grid.get_mut(&Vec2i::new(0, 2)).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn test_grid_index_out_of_bounds() {
+        let grid: Grid<char> = "ab\ncd\n".parse().unwrap();
+        let _ = grid[Vec2i::new(2, 0)];
+    }
+
+    #[test]
+    fn test_grid_combinators() {
+        let grid: Grid<char> = "12\n34\n".parse().unwrap();
+        let numbers = grid.map(|c| c.to_digit(10).unwrap() as i64);
+        assert_eq!(
+            numbers.iter().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3, 4]
+        );
+        assert_eq!(numbers.fold(0, |acc, pos, &n| acc + pos.x * n), 2 + 4);
+        let doubled = &numbers + &numbers;
+        assert_eq!(&doubled - &numbers, numbers);
+        assert_eq!((&numbers * &numbers)[Vec2i::new(1, 1)], 16);
+        let mut sum = numbers.clone();
+        sum += &doubled;
+        assert_eq!(sum, numbers.map(|n| 3 * n));
+        assert_eq!(
+            grid.zip_with(&numbers, |c, n| format!("{c}{n}"))[Vec2i::new(1, 0)],
+            "22"
+        );
+    }
+}
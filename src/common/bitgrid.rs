@@ -0,0 +1,412 @@
+use std::ops::{BitAndAssign, BitOrAssign, BitXorAssign, Index, Not};
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::common::{Grid, Vec2i};
+
+/// A boolean grid storing 64 cells per word, rows are padded to whole words.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitGrid {
+    pub size_x: usize,
+    pub size_y: usize,
+    words_per_row: usize,
+    bits: Vec<u64>,
+}
+
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ParseBitGridError {
+    #[error("{found:?} at ({x}, {y}) is neither '#' nor '.'")]
+    InvalidCell { x: usize, y: usize, found: char },
+    #[error("row {row} has {found} cells, expected {expected}")]
+    RaggedRow {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl FromStr for BitGrid {
+    type Err = ParseBitGridError;
+
+    /// Parses `#` as set and `.` as unset cells, blank lines are skipped.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rows: Vec<_> = s.lines().map(str::trim).filter(|l| !l.is_empty()).collect();
+        let size_x = rows.first().map_or(0, |r| r.chars().count());
+        let mut result = Self::new(size_x, rows.len());
+        for (y, row) in rows.iter().enumerate() {
+            let found = row.chars().count();
+            if found != size_x {
+                return Err(ParseBitGridError::RaggedRow {
+                    row: y,
+                    expected: size_x,
+                    found,
+                });
+            }
+            for (x, c) in row.chars().enumerate() {
+                match c {
+                    '#' => result.set(&Vec2i::new(x as i64, y as i64), true),
+                    '.' => {}
+                    _ => {
+                        return Err(ParseBitGridError::InvalidCell { x, y, found: c });
+                    }
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl<T> From<&Grid<T>> for BitGrid
+where
+    T: Copy + Into<bool>,
+{
+    fn from(grid: &Grid<T>) -> Self {
+        Self::from_grid(grid, |&t| t.into())
+    }
+}
+
+impl BitGrid {
+    const WORD_BITS: usize = u64::BITS as usize;
+
+    pub fn new(size_x: usize, size_y: usize) -> Self {
+        let words_per_row = size_x.div_ceil(Self::WORD_BITS);
+        Self {
+            size_x,
+            size_y,
+            words_per_row,
+            bits: vec![0; words_per_row * size_y],
+        }
+    }
+
+    pub fn from_grid<T>(grid: &Grid<T>, f: impl Fn(&T) -> bool) -> Self {
+        let mut result = Self::new(grid.size_x, grid.size_y);
+        for (pos, t) in grid.pos_iter() {
+            if f(t) {
+                result.set(&pos, true);
+            }
+        }
+        result
+    }
+
+    pub fn in_bounds(&self, pos: &Vec2i) -> bool {
+        pos.x >= 0 && (pos.x as usize) < self.size_x && pos.y >= 0 && (pos.y as usize) < self.size_y
+    }
+
+    fn out_of_bounds(&self, pos: &Vec2i) -> ! {
+        panic!(
+            "position ({}, {}) is out of bounds for a {}x{} grid",
+            pos.x, pos.y, self.size_x, self.size_y
+        )
+    }
+
+    fn word_and_mask(&self, pos: &Vec2i) -> (usize, u64) {
+        if !self.in_bounds(pos) {
+            self.out_of_bounds(pos);
+        }
+        let (x, y) = (pos.x as usize, pos.y as usize);
+        (
+            y * self.words_per_row + x / Self::WORD_BITS,
+            1 << (x % Self::WORD_BITS),
+        )
+    }
+
+    pub fn get(&self, pos: &Vec2i) -> Option<bool> {
+        self.in_bounds(pos).then(|| {
+            let (word, mask) = self.word_and_mask(pos);
+            self.bits[word] & mask != 0
+        })
+    }
+
+    pub fn mod_get(&self, pos: &Vec2i) -> bool {
+        let x = pos.x.rem_euclid(self.size_x as i64);
+        let y = pos.y.rem_euclid(self.size_y as i64);
+        self[Vec2i::new(x, y)]
+    }
+
+    pub fn set(&mut self, pos: &Vec2i, value: bool) {
+        let (word, mask) = self.word_and_mask(pos);
+        if value {
+            self.bits[word] |= mask;
+        } else {
+            self.bits[word] &= !mask;
+        }
+    }
+
+    pub fn toggle(&mut self, pos: &Vec2i) {
+        let (word, mask) = self.word_and_mask(pos);
+        self.bits[word] ^= mask;
+    }
+
+    /// The words making up row `y`, the padding bits of the last word are always zero.
+    pub fn row_words(&self, y: usize) -> &[u64] {
+        &self.bits[y * self.words_per_row..(y + 1) * self.words_per_row]
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.bits.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn count_ones_row(&self, y: usize) -> usize {
+        self.row_words(y)
+            .iter()
+            .map(|w| w.count_ones() as usize)
+            .sum()
+    }
+
+    /// Applies `op` to all words covering the rectangle spanned by `from` and `to` (inclusive),
+    /// passing the mask of the affected bits.
+    fn apply_rect(&mut self, from: &Vec2i, to: &Vec2i, op: impl Fn(&mut u64, u64)) {
+        let (min, max) = (from.inf(to), from.sup(to));
+        for corner in [min, max] {
+            if !self.in_bounds(&corner) {
+                self.out_of_bounds(&corner);
+            }
+        }
+        let (x0, x1) = (min.x as usize, max.x as usize);
+        let (y0, y1) = (min.y as usize, max.y as usize);
+
+        let (first_word, last_word) = (x0 / Self::WORD_BITS, x1 / Self::WORD_BITS);
+        for y in y0..=y1 {
+            let row = y * self.words_per_row;
+            for w in first_word..=last_word {
+                let lo = if w == first_word {
+                    x0 % Self::WORD_BITS
+                } else {
+                    0
+                };
+                let hi = if w == last_word {
+                    x1 % Self::WORD_BITS
+                } else {
+                    Self::WORD_BITS - 1
+                };
+                let mask = (u64::MAX >> (Self::WORD_BITS - 1 - hi)) & (u64::MAX << lo);
+                op(&mut self.bits[row + w], mask);
+            }
+        }
+    }
+
+    pub fn set_rect(&mut self, from: &Vec2i, to: &Vec2i) {
+        self.apply_rect(from, to, |w, mask| *w |= mask);
+    }
+
+    pub fn clear_rect(&mut self, from: &Vec2i, to: &Vec2i) {
+        self.apply_rect(from, to, |w, mask| *w &= !mask);
+    }
+
+    pub fn toggle_rect(&mut self, from: &Vec2i, to: &Vec2i) {
+        self.apply_rect(from, to, |w, mask| *w ^= mask);
+    }
+
+    /// Combines row `y` of this grid with row `other_y` of `other` using `op` on whole words.
+    ///
+    /// Both grids must have the same width.
+    pub fn combine_row(
+        &mut self,
+        y: usize,
+        other: &BitGrid,
+        other_y: usize,
+        op: impl Fn(u64, u64) -> u64,
+    ) {
+        assert_eq!(self.size_x, other.size_x, "grid widths differ");
+        let row = y * self.words_per_row;
+        for (i, &o) in other.row_words(other_y).iter().enumerate() {
+            self.bits[row + i] = op(self.bits[row + i], o);
+        }
+        self.clear_padding(y);
+    }
+
+    fn clear_padding(&mut self, y: usize) {
+        let used = self.size_x % Self::WORD_BITS;
+        if used != 0 {
+            self.bits[(y + 1) * self.words_per_row - 1] &= (1 << used) - 1;
+        }
+    }
+
+    fn combine(&mut self, other: &BitGrid, op: impl Fn(u64, u64) -> u64) {
+        assert_eq!(
+            (self.size_x, self.size_y),
+            (other.size_x, other.size_y),
+            "grid sizes differ"
+        );
+        for (a, &b) in self.bits.iter_mut().zip(&other.bits) {
+            *a = op(*a, b);
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        self.pos_iter().map(|(_, b)| b)
+    }
+
+    pub fn iter_col(&self, x: i64) -> impl Iterator<Item = bool> + '_ {
+        (0..self.size_y).map(move |y| self[Vec2i::new(x, y as i64)])
+    }
+
+    pub fn iter_row(&self, y: i64) -> impl Iterator<Item = bool> + '_ {
+        (0..self.size_x).map(move |x| self[Vec2i::new(x as i64, y)])
+    }
+
+    pub fn pos_iter(&self) -> impl Iterator<Item = (Vec2i, bool)> + '_ {
+        (0..self.size_y).flat_map(move |y| {
+            (0..self.size_x).map(move |x| {
+                let pos = Vec2i::new(x as i64, y as i64);
+                (pos, self[pos])
+            })
+        })
+    }
+
+    /// Positions of all set cells.
+    pub fn ones(&self) -> impl Iterator<Item = Vec2i> + '_ {
+        (0..self.size_y).flat_map(move |y| {
+            self.row_words(y)
+                .iter()
+                .enumerate()
+                .flat_map(move |(i, &w)| {
+                    let mut w = w;
+                    std::iter::from_fn(move || {
+                        if w == 0 {
+                            return None;
+                        }
+                        let bit = w.trailing_zeros() as usize;
+                        w &= w - 1;
+                        Some(Vec2i::new((i * Self::WORD_BITS + bit) as i64, y as i64))
+                    })
+                })
+        })
+    }
+}
+
+impl Index<Vec2i> for BitGrid {
+    type Output = bool;
+
+    fn index(&self, index: Vec2i) -> &Self::Output {
+        if self
+            .get(&index)
+            .unwrap_or_else(|| self.out_of_bounds(&index))
+        {
+            &true
+        } else {
+            &false
+        }
+    }
+}
+
+impl BitAndAssign<&BitGrid> for BitGrid {
+    fn bitand_assign(&mut self, rhs: &BitGrid) {
+        self.combine(rhs, |a, b| a & b);
+    }
+}
+
+impl BitOrAssign<&BitGrid> for BitGrid {
+    fn bitor_assign(&mut self, rhs: &BitGrid) {
+        self.combine(rhs, |a, b| a | b);
+    }
+}
+
+impl BitXorAssign<&BitGrid> for BitGrid {
+    fn bitxor_assign(&mut self, rhs: &BitGrid) {
+        self.combine(rhs, |a, b| a ^ b);
+    }
+}
+
+impl Not for BitGrid {
+    type Output = BitGrid;
+
+    fn not(mut self) -> Self::Output {
+        for w in &mut self.bits {
+            *w = !*w;
+        }
+        for y in 0..self.size_y {
+            self.clear_padding(y);
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_and_index() {
+        let grid: BitGrid = ".#.\n##.".parse().unwrap();
+        assert_eq!(grid.count_ones(), 3);
+        assert!(grid[Vec2i::new(1, 0)]);
+        assert!(!grid[Vec2i::new(2, 1)]);
+        assert_eq!(
+            grid.ones().collect::<Vec<_>>(),
+            vec![Vec2i::new(1, 0), Vec2i::new(0, 1), Vec2i::new(1, 1)]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            ".#.\n#x.".parse::<BitGrid>(),
+            Err(ParseBitGridError::InvalidCell {
+                x: 1,
+                y: 1,
+                found: 'x'
+            })
+        );
+        assert_eq!(
+            ".#.\n#.".parse::<BitGrid>(),
+            Err(ParseBitGridError::RaggedRow {
+                row: 1,
+                expected: 3,
+                found: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_rect_ops() {
+        let mut grid = BitGrid::new(200, 3);
+        grid.set_rect(&Vec2i::new(10, 0), &Vec2i::new(150, 2));
+        assert_eq!(grid.count_ones(), 141 * 3);
+        grid.toggle_rect(&Vec2i::new(0, 1), &Vec2i::new(199, 1));
+        assert_eq!(grid.count_ones_row(1), 200 - 141);
+        grid.clear_rect(&Vec2i::new(63, 0), &Vec2i::new(64, 0));
+        assert_eq!(grid.count_ones_row(0), 139);
+        assert_eq!(grid.get(&Vec2i::new(64, 0)), Some(false));
+        assert_eq!(grid.get(&Vec2i::new(65, 0)), Some(true));
+        assert_eq!(grid.get(&Vec2i::new(200, 0)), None);
+        assert_eq!(grid.get(&Vec2i::new(0, -1)), None);
+    }
+
+    #[test]
+    fn test_bit_ops() {
+        let a: BitGrid = "##..\n#.#.".parse().unwrap();
+        let b: BitGrid = "#.#.\n####".parse().unwrap();
+        let mut c = a.clone();
+        c &= &b;
+        assert_eq!(c, "#...\n#.#.".parse().unwrap());
+        let mut c = a.clone();
+        c ^= &b;
+        assert_eq!(c, ".##.\n.#.#".parse().unwrap());
+        assert_eq!((!a.clone()).count_ones(), 4);
+        let mut c = a;
+        c.combine_row(0, &b, 1, |x, y| x | y);
+        assert_eq!(c.count_ones_row(0), 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "position (64, 0) is out of bounds for a 64x2 grid")]
+    fn test_get_past_row_end() {
+        let _ = BitGrid::new(64, 2)[Vec2i::new(64, 0)];
+    }
+
+    #[test]
+    #[should_panic(expected = "position (5, 0) is out of bounds for a 5x1 grid")]
+    fn test_set_padding() {
+        BitGrid::new(5, 1).set(&Vec2i::new(5, 0), true);
+    }
+
+    #[test]
+    #[should_panic(expected = "position (-1, 0) is out of bounds for a 4x4 grid")]
+    fn test_rect_negative_corner() {
+        BitGrid::new(4, 4).set_rect(&Vec2i::new(-1, 0), &Vec2i::new(2, 2));
+    }
+}
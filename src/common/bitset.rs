@@ -0,0 +1,409 @@
+use std::iter::FusedIterator;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Sub, SubAssign};
+
+use crate::common::combinatorics::{checked_binomial, exact_size_hint};
+
+/// The words backing a [`BitSet`].
+pub trait BitStorage: AsRef<[u64]> + AsMut<[u64]> + Clone {
+    /// Storage for at least `bits` bits, all unset.
+    fn zeroed(bits: usize) -> Self;
+}
+
+impl<const W: usize> BitStorage for [u64; W] {
+    fn zeroed(bits: usize) -> Self {
+        assert!(
+            bits <= W * BitSet::<Self>::WORD_BITS,
+            "{bits} bits do not fit into {W} words"
+        );
+        [0; W]
+    }
+}
+
+impl BitStorage for Vec<u64> {
+    fn zeroed(bits: usize) -> Self {
+        vec![0; bits.div_ceil(BitSet::<Self>::WORD_BITS)]
+    }
+}
+
+/// A set of small non-negative integers, stored as one bit per element.
+///
+/// Set operations between two sets require them to have the same number of words.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BitSet<S> {
+    words: S,
+}
+
+/// A bitset with a fixed capacity of `64 * W` bits that lives on the stack.
+pub type FixedBitSet<const W: usize> = BitSet<[u64; W]>;
+
+/// A bitset with its capacity chosen at runtime.
+pub type HeapBitSet = BitSet<Vec<u64>>;
+
+impl<S: BitStorage> BitSet<S> {
+    const WORD_BITS: usize = u64::BITS as usize;
+
+    /// An empty set able to hold the elements `0..bits`.
+    pub fn new(bits: usize) -> Self {
+        Self {
+            words: S::zeroed(bits),
+        }
+    }
+
+    /// The set `0..bits`.
+    pub fn full(bits: usize) -> Self {
+        Self::from_indices(bits, 0..bits)
+    }
+
+    pub fn from_indices(bits: usize, indices: impl IntoIterator<Item = usize>) -> Self {
+        let mut set = Self::new(bits);
+        for i in indices {
+            set.insert(i);
+        }
+        set
+    }
+
+    pub fn words(&self) -> &[u64] {
+        self.words.as_ref()
+    }
+
+    /// The number of bits that can be stored.
+    pub fn capacity(&self) -> usize {
+        self.words().len() * Self::WORD_BITS
+    }
+
+    pub fn contains(&self, i: usize) -> bool {
+        self.words()
+            .get(i / Self::WORD_BITS)
+            .is_some_and(|w| (w >> (i % Self::WORD_BITS)) & 1 != 0)
+    }
+
+    /// Adds `i` and returns whether it was newly inserted.
+    pub fn insert(&mut self, i: usize) -> bool {
+        let was_set = self.contains(i);
+        self.words.as_mut()[i / Self::WORD_BITS] |= 1u64 << (i % Self::WORD_BITS);
+        !was_set
+    }
+
+    /// Removes `i` and returns whether it was present.
+    pub fn remove(&mut self, i: usize) -> bool {
+        let was_set = self.contains(i);
+        if was_set {
+            self.words.as_mut()[i / Self::WORD_BITS] &= !(1u64 << (i % Self::WORD_BITS));
+        }
+        was_set
+    }
+
+    pub fn toggle(&mut self, i: usize) {
+        self.words.as_mut()[i / Self::WORD_BITS] ^= 1u64 << (i % Self::WORD_BITS);
+    }
+
+    pub fn clear(&mut self) {
+        self.words.as_mut().fill(0);
+    }
+
+    pub fn len(&self) -> usize {
+        self.words().iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words().iter().all(|&w| w == 0)
+    }
+
+    /// The smallest element.
+    pub fn first(&self) -> Option<usize> {
+        self.words()
+            .iter()
+            .enumerate()
+            .find(|(_, &w)| w != 0)
+            .map(|(i, w)| i * Self::WORD_BITS + w.trailing_zeros() as usize)
+    }
+
+    /// The elements in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words().iter().enumerate().flat_map(|(i, &w)| {
+            let mut w = w;
+            std::iter::from_fn(move || {
+                (w != 0).then(|| {
+                    let bit = w.trailing_zeros() as usize;
+                    w &= w - 1;
+                    i * Self::WORD_BITS + bit
+                })
+            })
+        })
+    }
+
+    fn combine(&mut self, other: &Self, f: impl Fn(u64, u64) -> u64) {
+        assert_eq!(
+            self.words().len(),
+            other.words().len(),
+            "bitsets of different widths"
+        );
+        for (a, &b) in self.words.as_mut().iter_mut().zip(other.words()) {
+            *a = f(*a, b);
+        }
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        self | other
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        self & other
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        self - other
+    }
+
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        self ^ other
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.words()
+            .iter()
+            .zip(other.words())
+            .all(|(&a, &b)| a & !b == 0)
+    }
+
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.words()
+            .iter()
+            .zip(other.words())
+            .all(|(&a, &b)| a & b == 0)
+    }
+
+    /// The next larger set of the same size with all elements below `n`, in Gosper order.
+    ///
+    /// This is the multi-word generalization of [`next_bit_permutation`](crate::common::combinatorics::next_bit_permutation).
+    pub fn next_k_subset(&self, n: usize) -> Option<Self> {
+        let lowest = self.first()?;
+        let run = (lowest..).take_while(|&i| self.contains(i)).count();
+        if lowest + run >= n {
+            return None;
+        }
+
+        let mut next = self.clone();
+        for i in lowest..lowest + run {
+            next.remove(i);
+        }
+        next.insert(lowest + run);
+        for i in 0..run - 1 {
+            next.insert(i);
+        }
+        Some(next)
+    }
+
+    /// All subsets of `0..n` with `k` elements, in Gosper order.
+    pub fn k_subsets(n: usize, k: usize) -> KSubsets<S> {
+        KSubsets {
+            next: (k <= n).then(|| Self::from_indices(n, 0..k)),
+            n,
+            is_empty: k == 0,
+            remaining: checked_binomial(n, k),
+        }
+    }
+
+    /// All subsets of `self` in descending numeric order, from `self` itself down to the empty set.
+    pub fn submasks(&self) -> Submasks<S> {
+        Submasks {
+            mask: self.clone(),
+            next: Some(self.clone()),
+            remaining: 1usize.checked_shl(self.len() as u32),
+        }
+    }
+
+    /// Subtracts one from the set read as a big number, `false` if it was already empty.
+    fn decrement(&mut self) -> bool {
+        for w in self.words.as_mut() {
+            let (result, borrow) = w.overflowing_sub(1);
+            *w = result;
+            if !borrow {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Iterator returned by [`BitSet::k_subsets`].
+#[derive(Debug, Clone)]
+pub struct KSubsets<S> {
+    next: Option<BitSet<S>>,
+    n: usize,
+    is_empty: bool,
+    remaining: Option<usize>,
+}
+
+impl<S: BitStorage> Iterator for KSubsets<S> {
+    type Item = BitSet<S>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next.take()?;
+        if !self.is_empty {
+            self.next = current.next_k_subset(self.n);
+        }
+        if let Some(remaining) = &mut self.remaining {
+            *remaining -= 1;
+        }
+        Some(current)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        exact_size_hint(self.remaining)
+    }
+}
+
+impl<S> KSubsets<S> {
+    /// The number of subsets left, `None` if it does not fit in `usize`.
+    pub fn remaining(&self) -> Option<usize> {
+        self.remaining
+    }
+}
+
+impl<S: BitStorage> FusedIterator for KSubsets<S> {}
+
+/// Iterator returned by [`BitSet::submasks`].
+#[derive(Debug, Clone)]
+pub struct Submasks<S> {
+    mask: BitSet<S>,
+    next: Option<BitSet<S>>,
+    remaining: Option<usize>,
+}
+
+impl<S: BitStorage> Iterator for Submasks<S> {
+    type Item = BitSet<S>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next.take()?;
+        let mut next = current.clone();
+        if next.decrement() {
+            next &= &self.mask;
+            self.next = Some(next);
+        }
+        if let Some(remaining) = &mut self.remaining {
+            *remaining -= 1;
+        }
+        Some(current)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        exact_size_hint(self.remaining)
+    }
+}
+
+impl<S> Submasks<S> {
+    /// The number of submasks left, `None` if it does not fit in `usize`.
+    pub fn remaining(&self) -> Option<usize> {
+        self.remaining
+    }
+}
+
+impl<S: BitStorage> FusedIterator for Submasks<S> {}
+
+macro_rules! impl_bit_op {
+    ($op:ident, $fn:ident, $op_assign:ident, $fn_assign:ident, $f:expr) => {
+        impl<S: BitStorage> $op_assign<&BitSet<S>> for BitSet<S> {
+            fn $fn_assign(&mut self, rhs: &BitSet<S>) {
+                self.combine(rhs, $f);
+            }
+        }
+
+        impl<S: BitStorage> $op<&BitSet<S>> for &BitSet<S> {
+            type Output = BitSet<S>;
+
+            fn $fn(self, rhs: &BitSet<S>) -> BitSet<S> {
+                let mut result = self.clone();
+                result.combine(rhs, $f);
+                result
+            }
+        }
+    };
+}
+
+impl_bit_op!(BitAnd, bitand, BitAndAssign, bitand_assign, |a, b| a & b);
+impl_bit_op!(BitOr, bitor, BitOrAssign, bitor_assign, |a, b| a | b);
+impl_bit_op!(BitXor, bitxor, BitXorAssign, bitxor_assign, |a, b| a ^ b);
+impl_bit_op!(Sub, sub, SubAssign, sub_assign, |a, b| a & !b);
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::common::combinatorics::next_bit_permutation;
+
+    #[test]
+    fn test_set_algebra() {
+        let a = HeapBitSet::from_indices(130, [0, 3, 64, 129]);
+        let b = HeapBitSet::from_indices(130, [3, 4, 129]);
+        assert_eq!(a.capacity(), 192);
+        assert_eq!(a.union(&b).iter().collect_vec(), vec![0, 3, 4, 64, 129]);
+        assert_eq!(a.intersection(&b).iter().collect_vec(), vec![3, 129]);
+        assert_eq!((&a - &b).iter().collect_vec(), vec![0, 64]);
+        assert_eq!((&a ^ &b).len(), 3);
+        assert!(a.intersection(&b).is_subset(&a));
+        assert!(!a.is_disjoint(&b));
+        assert_eq!(a.first(), Some(0));
+    }
+
+    #[test]
+    fn test_k_subsets() {
+        let subsets = FixedBitSet::<1>::k_subsets(4, 2)
+            .map(|s| s.words()[0])
+            .collect_vec();
+        assert_eq!(
+            subsets,
+            vec![0b0011, 0b0101, 0b0110, 0b1001, 0b1010, 0b1100]
+        );
+        assert_eq!(FixedBitSet::<1>::k_subsets(4, 0).count(), 1);
+        assert_eq!(FixedBitSet::<1>::k_subsets(3, 4).count(), 0);
+
+        // crosses the word boundary and agrees with the primitive version
+        let wide = HeapBitSet::k_subsets(70, 2).collect_vec();
+        assert_eq!(HeapBitSet::k_subsets(70, 2).remaining(), Some(70 * 69 / 2));
+        assert_eq!(wide.len(), 70 * 69 / 2);
+        assert_eq!(wide.last().unwrap().iter().collect_vec(), vec![68, 69]);
+        let mut v = 0b111u128;
+        for s in HeapBitSet::k_subsets(100, 3).take(1000) {
+            assert_eq!(s.words()[0] as u128 | ((s.words()[1] as u128) << 64), v);
+            v = next_bit_permutation(v).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_submasks() {
+        let mask = FixedBitSet::<2>::from_indices(128, [1, 3, 100]);
+        let submasks = mask.submasks().collect_vec();
+        assert_eq!(mask.submasks().remaining(), Some(8));
+        assert_eq!(submasks.len(), 8);
+        assert!(submasks.iter().all(|s| s.is_subset(&mask)));
+        assert!(submasks.iter().all_unique());
+        assert!(submasks.last().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_huge_counts() {
+        let full = FixedBitSet::<2>::full(128);
+        let mut submasks = full.submasks();
+        assert_eq!(submasks.size_hint(), (usize::MAX, None));
+        assert_eq!(submasks.next(), Some(full));
+        assert_eq!(
+            FixedBitSet::<1>::full(63).submasks().size_hint(),
+            (1 << 63, Some(1 << 63))
+        );
+        assert_eq!(
+            FixedBitSet::<1>::k_subsets(64, 32).remaining(),
+            Some(1_832_624_140_942_590_534)
+        );
+        assert_eq!(
+            HeapBitSet::k_subsets(200, 100).size_hint(),
+            (usize::MAX, None)
+        );
+    }
+}
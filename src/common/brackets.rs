@@ -0,0 +1,279 @@
+//! Matching of nested brackets with any number of bracket pairs.
+//!
+//! With the single pair `()` the nesting depth is the floor of day 1 and the first
+//! [`Mismatch::UnmatchedClose`] is the first basement entry.
+
+use std::str::FromStr;
+
+use rustc_hash::FxHashMap;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum BracketSetError {
+    #[error("{0:?} is used by more than one bracket pair")]
+    DuplicateBracket(char),
+    #[error("bracket pairs need an even number of characters")]
+    OddLength,
+}
+
+/// The first place where the brackets of an input do not nest, positions are byte offsets.
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Mismatch {
+    #[error("{found:?} at {position} does not close {open:?} at {open_position}")]
+    WrongClose {
+        position: usize,
+        found: char,
+        open_position: usize,
+        open: char,
+    },
+    #[error("{found:?} at {position} closes nothing")]
+    UnmatchedClose { position: usize, found: char },
+    #[error("{open:?} at {position} is never closed")]
+    Unclosed { position: usize, open: char },
+}
+
+impl Mismatch {
+    pub fn position(&self) -> usize {
+        match *self {
+            Mismatch::WrongClose { position, .. }
+            | Mismatch::UnmatchedClose { position, .. }
+            | Mismatch::Unclosed { position, .. } => position,
+        }
+    }
+}
+
+/// A set of `(open, close)` bracket pairs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BracketSet {
+    pairs: Vec<(char, char)>,
+}
+
+impl BracketSet {
+    pub fn new(pairs: impl IntoIterator<Item = (char, char)>) -> Result<Self, BracketSetError> {
+        let pairs: Vec<_> = pairs.into_iter().collect();
+        let mut seen = vec![];
+        for c in pairs.iter().flat_map(|&(open, close)| [open, close]) {
+            if seen.contains(&c) {
+                return Err(BracketSetError::DuplicateBracket(c));
+            }
+            seen.push(c);
+        }
+        Ok(Self { pairs })
+    }
+
+    /// Only `(` and `)`, as in day 1.
+    pub fn parentheses() -> Self {
+        Self {
+            pairs: vec![('(', ')')],
+        }
+    }
+
+    /// `()`, `[]`, `{}` and `<>`.
+    pub fn standard() -> Self {
+        "()[]{}<>".parse().unwrap()
+    }
+
+    pub fn pairs(&self) -> &[(char, char)] {
+        &self.pairs
+    }
+
+    fn closing(&self, open: char) -> Option<char> {
+        self.pairs.iter().find(|p| p.0 == open).map(|p| p.1)
+    }
+
+    fn is_close(&self, c: char) -> bool {
+        self.pairs.iter().any(|p| p.1 == c)
+    }
+
+    /// Checks that all brackets in `input` nest properly.
+    pub fn check(&self, input: &str) -> Result<(), Mismatch> {
+        match self.analyze(input).first_mismatch {
+            Some(mismatch) => Err(mismatch),
+            None => Ok(()),
+        }
+    }
+
+    /// Matches the brackets of `input`, characters that are not brackets are ignored.
+    ///
+    /// After a mismatch the analysis goes on as if the offending closing bracket was missing, so
+    /// brackets after it are still matched.
+    pub fn analyze(&self, input: &str) -> BracketAnalysis {
+        let mut stack: Vec<(usize, char)> = vec![];
+        let mut mismatches = vec![];
+        let mut pairs = vec![];
+        let mut depth_histogram = vec![1];
+        let mut max_depth_position = None;
+        for (position, c) in input.char_indices() {
+            if self.closing(c).is_some() {
+                stack.push((position, c));
+                if stack.len() >= depth_histogram.len() {
+                    depth_histogram.push(0);
+                    max_depth_position = Some(position);
+                }
+            } else if self.is_close(c) {
+                match stack.last() {
+                    Some(&(open_position, open)) if self.closing(open) == Some(c) => {
+                        stack.pop();
+                        pairs.push((open_position, position));
+                    }
+                    Some(&(open_position, open)) => mismatches.push(Mismatch::WrongClose {
+                        position,
+                        found: c,
+                        open_position,
+                        open,
+                    }),
+                    None => mismatches.push(Mismatch::UnmatchedClose { position, found: c }),
+                }
+            } else {
+                continue;
+            }
+            depth_histogram[stack.len()] += 1;
+        }
+
+        let first_mismatch = mismatches.into_iter().next().or_else(|| {
+            stack
+                .first()
+                .map(|&(position, open)| Mismatch::Unclosed { position, open })
+        });
+        let partners = pairs
+            .iter()
+            .flat_map(|&(open, close)| [(open, close), (close, open)])
+            .collect();
+        BracketAnalysis {
+            pairs,
+            partners,
+            first_mismatch,
+            max_depth_position,
+            depth_histogram,
+            final_depth: stack.len(),
+        }
+    }
+}
+
+impl FromStr for BracketSet {
+    type Err = BracketSetError;
+
+    /// Parses consecutive characters as pairs, e.g. `()[]`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<_> = s.chars().collect();
+        if chars.len() % 2 != 0 {
+            return Err(BracketSetError::OddLength);
+        }
+        Self::new(chars.chunks(2).map(|p| (p[0], p[1])))
+    }
+}
+
+/// The result of [`BracketSet::analyze`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BracketAnalysis {
+    pairs: Vec<(usize, usize)>,
+    partners: FxHashMap<usize, usize>,
+    pub first_mismatch: Option<Mismatch>,
+    /// Where the deepest nesting is first reached.
+    pub max_depth_position: Option<usize>,
+    /// The number of brackets after which the nesting has each depth, index 0 also counts the
+    /// start.
+    pub depth_histogram: Vec<usize>,
+    /// The number of brackets still open at the end.
+    pub final_depth: usize,
+}
+
+impl BracketAnalysis {
+    pub fn is_valid(&self) -> bool {
+        self.first_mismatch.is_none()
+    }
+
+    /// The matched `(open, close)` positions, ordered by the closing position.
+    pub fn pairs(&self) -> &[(usize, usize)] {
+        &self.pairs
+    }
+
+    /// The position of the bracket matching the one at `position`.
+    pub fn partner(&self, position: usize) -> Option<usize> {
+        self.partners.get(&position).copied()
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.depth_histogram.len() - 1
+    }
+
+    /// The average depth over the start and every bracket.
+    pub fn mean_depth(&self) -> f64 {
+        let (weighted, count) = self
+            .depth_histogram
+            .iter()
+            .enumerate()
+            .fold((0, 0), |(weighted, count), (depth, &n)| {
+                (weighted + depth * n, count + n)
+            });
+        weighted as f64 / count as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_bracket_set() {
+        assert_eq!(BracketSet::standard().pairs().len(), 4);
+        assert_eq!(
+            "()(]".parse::<BracketSet>(),
+            Err(BracketSetError::DuplicateBracket('('))
+        );
+        assert_eq!("()[".parse::<BracketSet>(), Err(BracketSetError::OddLength));
+    }
+
+    #[test]
+    fn test_valid() {
+        let analysis = BracketSet::standard().analyze("a(b[c]{}) <x>");
+        assert!(analysis.is_valid());
+        assert_eq!(analysis.pairs(), &[(3, 5), (6, 7), (1, 8), (10, 12)]);
+        assert_eq!(analysis.partner(1), Some(8));
+        assert_eq!(analysis.partner(5), Some(3));
+        assert_eq!(analysis.partner(0), None);
+        assert_eq!(analysis.max_depth(), 2);
+        assert_eq!(analysis.max_depth_position, Some(3));
+        assert_eq!(analysis.depth_histogram, vec![3, 4, 2]);
+        assert_eq!(analysis.mean_depth(), 8.0 / 9.0);
+    }
+
+    #[test]
+    fn test_mismatches() {
+        let brackets = BracketSet::standard();
+        assert_eq!(
+            brackets.check("([)]"),
+            Err(Mismatch::WrongClose {
+                position: 2,
+                found: ')',
+                open_position: 1,
+                open: '['
+            })
+        );
+        assert_eq!(
+            brackets.check("()]"),
+            Err(Mismatch::UnmatchedClose {
+                position: 2,
+                found: ']'
+            })
+        );
+        let analysis = brackets.analyze("{(}");
+        assert_eq!(analysis.first_mismatch.unwrap().position(), 2);
+        assert_eq!(analysis.final_depth, 2);
+        assert_eq!(
+            brackets.check("{()"),
+            Err(Mismatch::Unclosed {
+                position: 0,
+                open: '{'
+            })
+        );
+    }
+
+    #[test]
+    fn test_day1() {
+        let analysis = BracketSet::parentheses().analyze("()())(");
+        assert_eq!(analysis.first_mismatch.unwrap().position() + 1, 5);
+    }
+}
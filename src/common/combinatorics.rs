@@ -0,0 +1,834 @@
+//! Enumeration of compositions, subsets, permutations and Gray codes.
+//!
+//! Every iterator yields its items in lexicographic order unless noted otherwise and knows
+//! exactly how many items are left, as long as that number fits in `usize`. The `rank_*` and
+//! `unrank_*` functions convert between an item and its position in that order.
+
+use std::iter::FusedIterator;
+
+use num::traits::{CheckedShr, WrappingAdd, WrappingSub};
+use num::{PrimInt, ToPrimitive, Unsigned};
+
+/// The binomial coefficient `n choose k`, 0 if `k > n`. Panics if it does not fit in `usize`.
+pub fn binomial(n: usize, k: usize) -> usize {
+    checked_binomial(n, k).expect("binomial coefficient overflows usize")
+}
+
+/// The binomial coefficient `n choose k`, `None` if it does not fit in `usize`.
+pub fn checked_binomial(n: usize, k: usize) -> Option<usize> {
+    if k > n {
+        return Some(0);
+    }
+
+    // the partial products are binomial coefficients growing towards the result, so they fit in
+    // usize and multiplying one of them by a usize fits in u128
+    let k = k.min(n - k);
+    (0..k).try_fold(1usize, |acc, i| {
+        (acc as u128 * (n - i) as u128 / (i + 1) as u128)
+            .try_into()
+            .ok()
+    })
+}
+
+/// The size hint of an iterator with `remaining` items, `None` if that count exceeds `usize`.
+pub(crate) fn exact_size_hint(remaining: Option<usize>) -> (usize, Option<usize>) {
+    match remaining {
+        Some(n) => (n, Some(n)),
+        None => (usize::MAX, None),
+    }
+}
+
+/// The number of ordered selections of `k` out of `n` elements, `n! / (n - k)!`. Panics if it
+/// does not fit in `usize`.
+pub fn falling_factorial(n: usize, k: usize) -> usize {
+    checked_falling_factorial(n, k).expect("falling factorial overflows usize")
+}
+
+/// The number of ordered selections of `k` out of `n` elements, `None` if it does not fit in
+/// `usize`.
+pub fn checked_falling_factorial(n: usize, k: usize) -> Option<usize> {
+    if k > n {
+        return Some(0);
+    }
+
+    (n - k + 1..=n).try_fold(1usize, |acc, v| acc.checked_mul(v))
+}
+
+/// The next larger integer with the same number of set bits.
+pub fn next_bit_permutation<T: Unsigned + PrimInt + CheckedShr + WrappingAdd + WrappingSub>(
+    v: T,
+) -> Option<T> {
+    // https://graphics.stanford.edu/~seander/bithacks.html#NextBitPermutation
+    if v.is_zero() || v == T::max_value() {
+        return None;
+    }
+
+    // t gets v's least significant 0 bits set to 1
+    let one = T::one();
+    let t = v | (v - one);
+    // Next set to 1 the most significant bit to change,
+    // set to 0 the least significant ones, and add the necessary 1 bits.
+    let tp1 = t.wrapping_add(&one);
+    let w = tp1
+        | ((!t & tp1)
+            .wrapping_sub(&one)
+            .checked_shr((v.trailing_zeros() + 1) as _)
+            .unwrap_or_else(T::zero));
+    if w > v {
+        Some(w)
+    } else {
+        None
+    }
+}
+
+/// All sequences of `len` non-negative numbers adding up to `sum`.
+#[derive(Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct ConstantSumIter<T> {
+    len: usize,
+    sum: T,
+    last: Option<Result<Vec<T>, ()>>,
+    remaining: Option<usize>,
+}
+
+impl<T: Unsigned + Copy + ToPrimitive> ConstantSumIter<T> {
+    pub fn new(len: usize, sum: T) -> Self {
+        let remaining = if len == 0 {
+            Some(1)
+        } else {
+            sum.to_usize()
+                .and_then(|sum| sum.checked_add(len - 1))
+                .and_then(|n| checked_binomial(n, len - 1))
+        };
+        Self {
+            len,
+            sum,
+            last: None,
+            remaining,
+        }
+    }
+}
+
+impl<T: Unsigned + Copy + ToPrimitive> Iterator for ConstantSumIter<T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = match &mut self.last {
+            Some(Err(_)) => None,
+            None => {
+                let mut next = vec![T::zero(); self.len];
+                if self.len > 0 {
+                    next[self.len - 1] = self.sum;
+                    self.last = Some(Ok(next.clone()));
+                } else {
+                    self.last = Some(Err(()));
+                }
+                Some(next)
+            }
+            Some(last) => {
+                let last = last.as_mut().unwrap();
+                if last[0] == self.sum {
+                    self.last = Some(Err(()));
+                    return None;
+                }
+
+                debug_assert!(!self.sum.is_zero() && self.len >= 2);
+                let last_index = last.len() - 1;
+                let mut remaining_sum = T::zero();
+                for i in (1..=last_index).rev() {
+                    remaining_sum = remaining_sum + last[i];
+                    if !remaining_sum.is_zero() {
+                        debug_assert!(last[i] == remaining_sum);
+                        last[i - 1] = last[i - 1] + T::one();
+                        for x in &mut last[i..last_index] {
+                            *x = T::zero();
+                        }
+                        // TODO: maybe cache i-1 as the next starting index for the loop if this will set the last element to zero
+                        last[last_index] = remaining_sum - T::one();
+                        break;
+                    }
+                }
+
+                Some(last.clone())
+            }
+        };
+        if let (Some(_), Some(remaining)) = (&next, &mut self.remaining) {
+            *remaining -= 1;
+        }
+        next
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        exact_size_hint(self.remaining)
+    }
+}
+
+impl<T> ConstantSumIter<T> {
+    /// The number of sequences left, `None` if it does not fit in `usize`.
+    pub fn remaining(&self) -> Option<usize> {
+        self.remaining
+    }
+}
+
+impl<T: Unsigned + Copy + ToPrimitive> FusedIterator for ConstantSumIter<T> {}
+
+pub fn constant_sum_sequences<T: Unsigned + Copy + ToPrimitive>(
+    len: usize,
+    sum: T,
+) -> ConstantSumIter<T> {
+    ConstantSumIter::new(len, sum)
+}
+
+/// All sequences adding up to `sum` where the `i`-th element lies in `0..=bounds[i]`.
+#[derive(Debug, Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct BoundedCompositions {
+    bounds: Vec<usize>,
+    next: Option<Vec<usize>>,
+    remaining: Option<usize>,
+}
+
+impl BoundedCompositions {
+    pub fn new(sum: usize, bounds: &[usize]) -> Self {
+        // ways[s] is the number of ways to distribute s over the bounds seen so far, `None` if it
+        // exceeds usize, each new bound sums a sliding window over the previous counts
+        let mut ways = vec![Some(0); sum + 1];
+        ways[0] = Some(1);
+        for &bound in bounds {
+            let mut window = 0u128;
+            let mut overflowed = 0;
+            let mut next = vec![Some(0); sum + 1];
+            for s in 0..=sum {
+                match ways[s] {
+                    Some(w) => window += w as u128,
+                    None => overflowed += 1,
+                }
+                if s > bound {
+                    match ways[s - bound - 1] {
+                        Some(w) => window -= w as u128,
+                        None => overflowed -= 1,
+                    }
+                }
+                next[s] = if overflowed == 0 {
+                    window.try_into().ok()
+                } else {
+                    None
+                };
+            }
+            ways = next;
+        }
+
+        let mut first = vec![0; bounds.len()];
+        let fits = Self::fill_smallest(&mut first, bounds, sum);
+        Self {
+            bounds: bounds.to_vec(),
+            next: fits.then_some(first),
+            remaining: ways[sum],
+        }
+    }
+
+    /// Distributes `sum` over `values` as far to the back as possible, which gives the
+    /// lexicographically smallest result.
+    fn fill_smallest(values: &mut [usize], bounds: &[usize], sum: usize) -> bool {
+        let mut sum = sum;
+        for (v, &bound) in values.iter_mut().zip(bounds).rev() {
+            *v = sum.min(bound);
+            sum -= *v;
+        }
+        sum == 0
+    }
+}
+
+impl Iterator for BoundedCompositions {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next.take()?;
+        let mut next = current.clone();
+        let mut suffix_sum = 0;
+        for i in (0..next.len()).rev() {
+            if suffix_sum > 0 && next[i] < self.bounds[i] {
+                next[i] += 1;
+                Self::fill_smallest(&mut next[i + 1..], &self.bounds[i + 1..], suffix_sum - 1);
+                self.next = Some(next);
+                break;
+            }
+            suffix_sum += next[i];
+        }
+        if let Some(remaining) = &mut self.remaining {
+            *remaining -= 1;
+        }
+        Some(current)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        exact_size_hint(self.remaining)
+    }
+}
+
+impl BoundedCompositions {
+    /// The number of sequences left, `None` if it does not fit in `usize`.
+    pub fn remaining(&self) -> Option<usize> {
+        self.remaining
+    }
+}
+
+impl FusedIterator for BoundedCompositions {}
+
+pub fn bounded_compositions(sum: usize, bounds: &[usize]) -> BoundedCompositions {
+    BoundedCompositions::new(sum, bounds)
+}
+
+/// All sorted `k`-element subsets of `0..n`.
+#[derive(Debug, Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct Combinations {
+    n: usize,
+    next: Option<Vec<usize>>,
+    remaining: Option<usize>,
+}
+
+impl Iterator for Combinations {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next.take()?;
+        let k = current.len();
+        if let Some(i) = (0..k).rev().find(|&i| current[i] < self.n - k + i) {
+            let mut next = current.clone();
+            next[i] += 1;
+            for j in i + 1..k {
+                next[j] = next[j - 1] + 1;
+            }
+            self.next = Some(next);
+        }
+        if let Some(remaining) = &mut self.remaining {
+            *remaining -= 1;
+        }
+        Some(current)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        exact_size_hint(self.remaining)
+    }
+}
+
+impl Combinations {
+    /// The number of subsets left, `None` if it does not fit in `usize`.
+    pub fn remaining(&self) -> Option<usize> {
+        self.remaining
+    }
+}
+
+impl FusedIterator for Combinations {}
+
+pub fn k_subsets(n: usize, k: usize) -> Combinations {
+    Combinations {
+        n,
+        next: (k <= n).then(|| (0..k).collect()),
+        remaining: checked_binomial(n, k),
+    }
+}
+
+/// The position of the sorted `subset` of `0..n` in the order of [`k_subsets`], `None` if it
+/// does not fit in `usize`.
+pub fn rank_k_subset(n: usize, subset: &[usize]) -> Option<usize> {
+    let k = subset.len();
+    let mut rank = 0usize;
+    let mut start = 0;
+    for (i, &c) in subset.iter().enumerate() {
+        for v in start..c {
+            rank = rank.checked_add(checked_binomial(n - 1 - v, k - 1 - i)?)?;
+        }
+        start = c + 1;
+    }
+    Some(rank)
+}
+
+/// The `rank`-th item of [`k_subsets`], if there are that many.
+pub fn unrank_k_subset(n: usize, k: usize, rank: usize) -> Option<Vec<usize>> {
+    if checked_binomial(n, k).is_some_and(|count| rank >= count) {
+        return None;
+    }
+
+    let mut rank = rank;
+    let mut subset = Vec::with_capacity(k);
+    let mut v = 0;
+    for i in 0..k {
+        loop {
+            // a count larger than usize::MAX holds every remaining rank
+            match checked_binomial(n - 1 - v, k - 1 - i) {
+                Some(count) if rank >= count => rank -= count,
+                _ => break,
+            }
+            v += 1;
+        }
+        subset.push(v);
+        v += 1;
+    }
+    Some(subset)
+}
+
+/// All sequences of `k` distinct elements of `0..n`.
+#[derive(Debug, Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct Permutations {
+    n: usize,
+    next: Option<Vec<usize>>,
+    remaining: Option<usize>,
+}
+
+impl Iterator for Permutations {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next.take()?;
+        let mut used = vec![false; self.n];
+        for &v in &current {
+            used[v] = true;
+        }
+
+        let mut next = current.clone();
+        for i in (0..next.len()).rev() {
+            used[next[i]] = false;
+            if let Some(v) = (next[i] + 1..self.n).find(|&v| !used[v]) {
+                next[i] = v;
+                used[v] = true;
+                let mut free = (0..self.n).filter(|&v| !used[v]);
+                for x in &mut next[i + 1..] {
+                    *x = free.next().unwrap();
+                }
+                self.next = Some(next);
+                break;
+            }
+        }
+        if let Some(remaining) = &mut self.remaining {
+            *remaining -= 1;
+        }
+        Some(current)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        exact_size_hint(self.remaining)
+    }
+}
+
+impl Permutations {
+    /// The number of permutations left, `None` if it does not fit in `usize`.
+    pub fn remaining(&self) -> Option<usize> {
+        self.remaining
+    }
+}
+
+impl FusedIterator for Permutations {}
+
+pub fn k_permutations(n: usize, k: usize) -> Permutations {
+    Permutations {
+        n,
+        next: (k <= n).then(|| (0..k).collect()),
+        remaining: checked_falling_factorial(n, k),
+    }
+}
+
+/// The position of `permutation`, a sequence of distinct elements of `0..n`, in the order of
+/// [`k_permutations`], `None` if it does not fit in `usize`.
+pub fn rank_k_permutation(n: usize, permutation: &[usize]) -> Option<usize> {
+    let k = permutation.len();
+    permutation
+        .iter()
+        .enumerate()
+        .try_fold(0usize, |rank, (i, &p)| {
+            let smaller_unused = p - permutation[..i].iter().filter(|&&q| q < p).count();
+            if smaller_unused == 0 {
+                return Some(rank);
+            }
+            checked_falling_factorial(n - 1 - i, k - 1 - i)?
+                .checked_mul(smaller_unused)?
+                .checked_add(rank)
+        })
+}
+
+/// The `rank`-th item of [`k_permutations`], if there are that many.
+pub fn unrank_k_permutation(n: usize, k: usize, rank: usize) -> Option<Vec<usize>> {
+    if checked_falling_factorial(n, k).is_some_and(|count| rank >= count) {
+        return None;
+    }
+
+    let mut rank = rank;
+    let mut unused: Vec<usize> = (0..n).collect();
+    Some(
+        (0..k)
+            .map(|i| {
+                // a block larger than usize::MAX holds every remaining rank
+                let (index, rest) = match checked_falling_factorial(n - 1 - i, k - 1 - i) {
+                    Some(block) => (rank / block, rank % block),
+                    None => (0, rank),
+                };
+                rank = rest;
+                unused.remove(index)
+            })
+            .collect(),
+    )
+}
+
+/// The `i`-th code of the binary reflected Gray code, consecutive codes differ in one bit.
+pub fn gray_code(i: u64) -> u64 {
+    i ^ (i >> 1)
+}
+
+/// The position of `code` in the binary reflected Gray code, the inverse of [`gray_code`].
+pub fn gray_rank(code: u64) -> u64 {
+    let mut rank = code;
+    let mut shift = 1;
+    while shift < u64::BITS {
+        rank ^= rank >> shift;
+        shift <<= 1;
+    }
+    rank
+}
+
+/// All `bits`-bit numbers in Gray code order.
+#[derive(Debug, Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct GrayCodes {
+    next: u64,
+    end: u64,
+}
+
+impl Iterator for GrayCodes {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        (self.next < self.end).then(|| {
+            self.next += 1;
+            gray_code(self.next - 1)
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.end - self.next) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for GrayCodes {}
+
+impl FusedIterator for GrayCodes {}
+
+pub fn gray_codes(bits: u32) -> GrayCodes {
+    assert!(bits < u64::BITS, "at most 63 bits are supported");
+    GrayCodes {
+        next: 0,
+        end: 1 << bits,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_binomial() {
+        assert_eq!(binomial(5, 2), 10);
+        assert_eq!(binomial(3, 4), 0);
+        assert_eq!(checked_binomial(67, 33), Some(14_226_520_737_620_288_370));
+        assert_eq!(checked_binomial(68, 34), None);
+        assert_eq!(k_subsets(200, 100).size_hint(), (usize::MAX, None));
+
+        // the same counts through the bounded compositions, 0..=1 per element picks a subset
+        assert_eq!(
+            bounded_compositions(33, &[1; 67]).remaining(),
+            checked_binomial(67, 33)
+        );
+        let mut it = bounded_compositions(100, &[1; 200]);
+        assert_eq!(it.remaining(), None);
+        assert_eq!(it.size_hint(), (usize::MAX, None));
+        assert_eq!(it.next().map(|v| v.iter().sum::<usize>()), Some(100));
+        assert_eq!(bounded_compositions(2000, &[1000; 40]).remaining(), None);
+    }
+
+    #[test]
+    fn test_next_bit_permutation_1() {
+        assert_eq!(next_bit_permutation(0b0000_0000u8), None);
+    }
+
+    #[test]
+    fn test_next_bit_permutation_2() {
+        assert_eq!(next_bit_permutation(0b0000_0001u8), Some(0b0000_0010));
+    }
+
+    #[test]
+    fn test_next_bit_permutation_3() {
+        assert_eq!(next_bit_permutation(0b1000_0000u8), None);
+    }
+
+    #[test]
+    fn test_next_bit_permutation_4() {
+        assert_eq!(next_bit_permutation(0b0111_1111u8), Some(0b1011_1111));
+    }
+
+    #[test]
+    fn test_next_bit_permutation_5() {
+        assert_eq!(next_bit_permutation(0b1111_1110u8), None);
+    }
+
+    #[test]
+    fn test_next_bit_permutation_6() {
+        assert_eq!(next_bit_permutation(0b1111_1111u8), None);
+    }
+
+    #[test]
+    fn test_sum_iter_0() {
+        let mut it = constant_sum_sequences(0, 3u64);
+        assert_eq!(it.next(), Some(vec![]));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_sum_iter_1() {
+        let mut it = constant_sum_sequences(1, 3u64);
+        assert_eq!(it.next(), Some(vec![3]));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_sum_iter_2_0() {
+        let mut it = constant_sum_sequences(2, 0u64);
+        assert_eq!(it.next(), Some(vec![0, 0]));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_sum_iter_2_1() {
+        let mut it = constant_sum_sequences(2, 1u64);
+        assert_eq!(it.next(), Some(vec![0, 1]));
+        assert_eq!(it.next(), Some(vec![1, 0]));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_sum_iter_2_2() {
+        let mut it = constant_sum_sequences(2, 2u64);
+        assert_eq!(it.next(), Some(vec![0, 2]));
+        assert_eq!(it.next(), Some(vec![1, 1]));
+        assert_eq!(it.next(), Some(vec![2, 0]));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_sum_iter_3_0() {
+        let mut it = constant_sum_sequences(3, 0u64);
+        assert_eq!(it.next(), Some(vec![0, 0, 0]));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_sum_iter_3_1() {
+        let mut it = constant_sum_sequences(3, 1u64);
+        assert_eq!(it.next(), Some(vec![0, 0, 1]));
+        assert_eq!(it.next(), Some(vec![0, 1, 0]));
+        assert_eq!(it.next(), Some(vec![1, 0, 0]));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_sum_iter_3_2() {
+        let mut it = constant_sum_sequences(3, 2u64);
+        assert_eq!(it.next(), Some(vec![0, 0, 2]));
+        assert_eq!(it.next(), Some(vec![0, 1, 1]));
+        assert_eq!(it.next(), Some(vec![0, 2, 0]));
+        assert_eq!(it.next(), Some(vec![1, 0, 1]));
+        assert_eq!(it.next(), Some(vec![1, 1, 0]));
+        assert_eq!(it.next(), Some(vec![2, 0, 0]));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_sum_iter_3_3() {
+        let mut it = constant_sum_sequences(3, 3u64);
+        assert_eq!(it.next(), Some(vec![0, 0, 3]));
+        assert_eq!(it.next(), Some(vec![0, 1, 2]));
+        assert_eq!(it.next(), Some(vec![0, 2, 1]));
+        assert_eq!(it.next(), Some(vec![0, 3, 0]));
+        assert_eq!(it.next(), Some(vec![1, 0, 2]));
+        assert_eq!(it.next(), Some(vec![1, 1, 1]));
+        assert_eq!(it.next(), Some(vec![1, 2, 0]));
+        assert_eq!(it.next(), Some(vec![2, 0, 1]));
+        assert_eq!(it.next(), Some(vec![2, 1, 0]));
+        assert_eq!(it.next(), Some(vec![3, 0, 0]));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_sum_iter_3_4() {
+        let mut it = constant_sum_sequences(3, 4u64);
+        assert_eq!(it.next(), Some(vec![0, 0, 4]));
+        assert_eq!(it.next(), Some(vec![0, 1, 3]));
+        assert_eq!(it.next(), Some(vec![0, 2, 2]));
+        assert_eq!(it.next(), Some(vec![0, 3, 1]));
+        assert_eq!(it.next(), Some(vec![0, 4, 0]));
+        assert_eq!(it.next(), Some(vec![1, 0, 3]));
+        assert_eq!(it.next(), Some(vec![1, 1, 2]));
+        assert_eq!(it.next(), Some(vec![1, 2, 1]));
+        assert_eq!(it.next(), Some(vec![1, 3, 0]));
+        assert_eq!(it.next(), Some(vec![2, 0, 2]));
+        assert_eq!(it.next(), Some(vec![2, 1, 1]));
+        assert_eq!(it.next(), Some(vec![2, 2, 0]));
+        assert_eq!(it.next(), Some(vec![3, 0, 1]));
+        assert_eq!(it.next(), Some(vec![3, 1, 0]));
+        assert_eq!(it.next(), Some(vec![4, 0, 0]));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_sum_iter_4_0() {
+        let mut it = constant_sum_sequences(4, 0u64);
+        assert_eq!(it.next(), Some(vec![0, 0, 0, 0]));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_sum_iter_4_1() {
+        let mut it = constant_sum_sequences(4, 1u64);
+        assert_eq!(it.next(), Some(vec![0, 0, 0, 1]));
+        assert_eq!(it.next(), Some(vec![0, 0, 1, 0]));
+        assert_eq!(it.next(), Some(vec![0, 1, 0, 0]));
+        assert_eq!(it.next(), Some(vec![1, 0, 0, 0]));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_sum_iter_4_2() {
+        let mut it = constant_sum_sequences(4, 2u64);
+        assert_eq!(it.next(), Some(vec![0, 0, 0, 2]));
+        assert_eq!(it.next(), Some(vec![0, 0, 1, 1]));
+        assert_eq!(it.next(), Some(vec![0, 0, 2, 0]));
+        assert_eq!(it.next(), Some(vec![0, 1, 0, 1]));
+        assert_eq!(it.next(), Some(vec![0, 1, 1, 0]));
+        assert_eq!(it.next(), Some(vec![0, 2, 0, 0]));
+        assert_eq!(it.next(), Some(vec![1, 0, 0, 1]));
+        assert_eq!(it.next(), Some(vec![1, 0, 1, 0]));
+        assert_eq!(it.next(), Some(vec![1, 1, 0, 0]));
+        assert_eq!(it.next(), Some(vec![2, 0, 0, 0]));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_sum_iter_len() {
+        let mut it = constant_sum_sequences(4, 100u8);
+        assert_eq!(it.remaining(), Some(176851));
+        it.next();
+        assert_eq!(it.size_hint(), (176850, Some(176850)));
+        assert_eq!(it.count(), 176850);
+        assert_eq!(constant_sum_sequences(0, 5u8).remaining(), Some(1));
+
+        let mut it = constant_sum_sequences(40, 1000u64);
+        assert_eq!(it.remaining(), None);
+        assert_eq!(it.size_hint(), (usize::MAX, None));
+        let mut first = vec![0; 40];
+        first[39] = 1000;
+        assert_eq!(it.next(), Some(first));
+    }
+
+    #[test]
+    fn test_bounded_compositions() {
+        let it = bounded_compositions(3, &[1, 2, 2]);
+        assert_eq!(it.remaining(), Some(5));
+        assert_eq!(
+            it.collect_vec(),
+            vec![
+                vec![0, 1, 2],
+                vec![0, 2, 1],
+                vec![1, 0, 2],
+                vec![1, 1, 1],
+                vec![1, 2, 0]
+            ]
+        );
+        assert_eq!(bounded_compositions(6, &[1, 2, 2]).remaining(), Some(0));
+        assert_eq!(bounded_compositions(6, &[1, 2, 2]).next(), None);
+        assert_eq!(
+            bounded_compositions(7, &[3, 3, 3, 3]).collect_vec(),
+            constant_sum_sequences(4, 7usize)
+                .filter(|v| v.iter().all(|&x| x <= 3))
+                .collect_vec()
+        );
+    }
+
+    #[test]
+    fn test_k_subsets() {
+        let subsets = k_subsets(5, 3).collect_vec();
+        assert_eq!(subsets.len(), 10);
+        assert_eq!(subsets[0], vec![0, 1, 2]);
+        assert_eq!(subsets[9], vec![2, 3, 4]);
+        assert_eq!(subsets, (0..5).combinations(3).collect_vec());
+        for (rank, subset) in subsets.iter().enumerate() {
+            assert_eq!(rank_k_subset(5, subset), Some(rank));
+            assert_eq!(unrank_k_subset(5, 3, rank).as_ref(), Some(subset));
+        }
+        assert_eq!(unrank_k_subset(5, 3, 10), None);
+        assert_eq!(k_subsets(3, 0).collect_vec(), vec![Vec::<usize>::new()]);
+        assert_eq!(k_subsets(3, 4).remaining(), Some(0));
+
+        let first = (0..100).collect_vec();
+        assert_eq!(rank_k_subset(200, &first), Some(0));
+        assert_eq!(unrank_k_subset(200, 100, 0), Some(first));
+        let mut second = (0..100).collect_vec();
+        second[99] = 100;
+        assert_eq!(rank_k_subset(200, &second), Some(1));
+        assert_eq!(unrank_k_subset(200, 100, 1), Some(second));
+        assert_eq!(rank_k_subset(200, &(100..200).collect_vec()), None);
+    }
+
+    #[test]
+    fn test_k_permutations() {
+        let mut it = k_permutations(5, 3);
+        assert_eq!(it.remaining(), Some(60));
+        it.next();
+        assert_eq!(it.size_hint(), (59, Some(59)));
+        let permutations = k_permutations(5, 3).collect_vec();
+        assert_eq!(permutations, (0..5).permutations(3).collect_vec());
+        for (rank, permutation) in permutations.iter().enumerate() {
+            assert_eq!(rank_k_permutation(5, permutation), Some(rank));
+            assert_eq!(unrank_k_permutation(5, 3, rank).as_ref(), Some(permutation));
+        }
+        assert_eq!(unrank_k_permutation(5, 3, 60), None);
+    }
+
+    #[test]
+    fn test_huge_permutations() {
+        assert_eq!(
+            checked_falling_factorial(20, 20),
+            Some(2_432_902_008_176_640_000)
+        );
+        assert_eq!(checked_falling_factorial(21, 21), None);
+        let mut it = k_permutations(21, 21);
+        assert_eq!(it.size_hint(), (usize::MAX, None));
+        assert_eq!(it.remaining(), None);
+        assert_eq!(it.next(), Some((0..21).collect_vec()));
+
+        let first = (0..21).collect_vec();
+        assert_eq!(rank_k_permutation(21, &first), Some(0));
+        assert_eq!(unrank_k_permutation(21, 21, 0), Some(first));
+        let mut second = (0..21).collect_vec();
+        second.swap(19, 20);
+        assert_eq!(rank_k_permutation(21, &second), Some(1));
+        assert_eq!(unrank_k_permutation(21, 21, 1), Some(second));
+        assert_eq!(rank_k_permutation(21, &(0..21).rev().collect_vec()), None);
+    }
+
+    #[test]
+    fn test_gray_codes() {
+        let codes = gray_codes(4).collect_vec();
+        assert_eq!(codes.len(), 16);
+        assert!(codes.iter().all_unique());
+        assert!(codes.windows(2).all(|w| (w[0] ^ w[1]).count_ones() == 1));
+        assert!(codes
+            .iter()
+            .enumerate()
+            .all(|(i, &c)| gray_rank(c) == i as u64));
+        assert_eq!(gray_rank(gray_code(u64::MAX)), u64::MAX);
+    }
+}
@@ -0,0 +1,194 @@
+use std::hash::Hash;
+
+use rustc_hash::FxHashMap;
+
+/// The shape of the sequence `x, f(x), f(f(x)), ...` of a deterministic simulation: the states
+/// repeat with `period` from step `start` on.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Cycle {
+    pub start: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    /// The earliest step with the same state as step `n`.
+    pub fn reduce(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.period
+        }
+    }
+
+    /// The state after `n` steps, simulating at most `start + period` steps.
+    pub fn state_at<S: Clone>(&self, initial: &S, step: impl Fn(&S) -> S, n: usize) -> S {
+        nth_state(initial, step, self.reduce(n))
+    }
+}
+
+fn nth_state<S: Clone>(initial: &S, step: impl Fn(&S) -> S, n: usize) -> S {
+    (0..n).fold(initial.clone(), |state, _| step(&state))
+}
+
+/// Floyd's tortoise and hare, keeps two states in memory.
+pub fn floyd<S: Clone + Eq>(initial: &S, step: impl Fn(&S) -> S) -> Cycle {
+    let mut tortoise = step(initial);
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&step(&hare));
+    }
+
+    let mut start = 0;
+    tortoise = initial.clone();
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    let mut period = 1;
+    hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        period += 1;
+    }
+
+    Cycle { start, period }
+}
+
+/// Brent's algorithm, keeps two states in memory and needs fewer steps than [`floyd`].
+pub fn brent<S: Clone + Eq>(initial: &S, step: impl Fn(&S) -> S) -> Cycle {
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(initial);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = step(&hare);
+        period += 1;
+    }
+
+    let mut start = 0;
+    tortoise = initial.clone();
+    hare = nth_state(initial, &step, period);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    Cycle { start, period }
+}
+
+/// Every distinct state of a simulation, recorded until the first repetition.
+#[derive(Debug, Clone)]
+pub struct StateHistory<S> {
+    states: Vec<S>,
+    cycle: Cycle,
+}
+
+impl<S: Clone + Hash + Eq> StateHistory<S> {
+    /// Simulates until a state repeats, remembering every state on the way.
+    pub fn new(initial: S, step: impl Fn(&S) -> S) -> Self {
+        let mut seen = FxHashMap::default();
+        let mut states = vec![];
+        let mut state = initial;
+        loop {
+            if let Some(&start) = seen.get(&state) {
+                let period = states.len() - start;
+                return Self {
+                    states,
+                    cycle: Cycle { start, period },
+                };
+            }
+            seen.insert(state.clone(), states.len());
+            let next = step(&state);
+            states.push(state);
+            state = next;
+        }
+    }
+}
+
+impl<S> StateHistory<S> {
+    pub fn cycle(&self) -> Cycle {
+        self.cycle
+    }
+
+    /// The state after `n` steps, without simulating anything.
+    pub fn state_at(&self, n: usize) -> &S {
+        &self.states[self.cycle.reduce(n)]
+    }
+
+    /// The states before the first repetition, indexed by step.
+    pub fn states(&self) -> &[S] {
+        &self.states
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::common::{Grid, Vec2i};
+
+    fn step(x: &u64) -> u64 {
+        (x * x + 1) % 255
+    }
+
+    #[test]
+    fn test_cycle_detection() {
+        let history = StateHistory::new(3, step);
+        let cycle = history.cycle();
+        assert_eq!(floyd(&3, step), cycle);
+        assert_eq!(brent(&3, step), cycle);
+        assert_eq!(history.states().len(), cycle.start + cycle.period);
+        for n in [0, 1, 5, 17, 1000] {
+            assert_eq!(*history.state_at(n), nth_state(&3, step, n));
+        }
+        assert_eq!(
+            *history.state_at(1_000_000_007),
+            cycle.state_at(&3, step, 1_000_000_007)
+        );
+    }
+
+    #[test]
+    fn test_pure_cycle() {
+        let cycle = Cycle {
+            start: 0,
+            period: 1,
+        };
+        assert_eq!(floyd(&7u64, |&x| x), cycle);
+        assert_eq!(brent(&7u64, |&x| x), cycle);
+        assert_eq!(StateHistory::new(7u64, |&x| x).cycle(), cycle);
+    }
+
+    #[test]
+    fn test_grid_states() {
+        // shifts every row one cell to the right, a 3 wide grid repeats after three steps
+        let grid: Grid<char> = "#..\n.#.\n".parse().unwrap();
+        let shift = |g: &Grid<char>| {
+            let mut next = g.clone();
+            for (pos, &c) in g.pos_iter() {
+                let x = (pos.x + 1) % g.size_x as i64;
+                next[Vec2i::new(x, pos.y)] = c;
+            }
+            next
+        };
+        let expected = Cycle {
+            start: 0,
+            period: 3,
+        };
+        assert_eq!(StateHistory::new(grid.clone(), shift).cycle(), expected);
+        assert_eq!(brent(&grid, shift), expected);
+        assert_eq!(
+            StateHistory::new(grid.clone(), shift).state_at(1_000_000_000),
+            &shift(&grid)
+        );
+    }
+}
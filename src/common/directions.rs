@@ -0,0 +1,323 @@
+use std::str::FromStr;
+
+use crate::common::{normalize_direction, Direction, ParseDirectionError, Vec2i, Vec3i};
+
+/// The four cardinal directions plus the diagonals between them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Direction8 {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl From<Direction> for Direction8 {
+    fn from(value: Direction) -> Self {
+        match value {
+            Direction::North => Self::North,
+            Direction::South => Self::South,
+            Direction::East => Self::East,
+            Direction::West => Self::West,
+        }
+    }
+}
+
+impl TryFrom<Direction8> for Direction {
+    type Error = ();
+
+    fn try_from(value: Direction8) -> Result<Self, Self::Error> {
+        Ok(match value {
+            Direction8::North => Self::North,
+            Direction8::South => Self::South,
+            Direction8::East => Self::East,
+            Direction8::West => Self::West,
+            _ => {
+                return Err(());
+            }
+        })
+    }
+}
+
+impl TryFrom<char> for Direction8 {
+    type Error = ();
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        Direction::try_from(value).map(Self::from)
+    }
+}
+
+impl FromStr for Direction8 {
+    type Err = ParseDirectionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(d) = s.parse::<Direction>() {
+            return Ok(d.into());
+        }
+
+        Ok(match normalize_direction(s).as_str() {
+            "ne" | "northeast" | "ur" | "upright" => Self::NorthEast,
+            "se" | "southeast" | "dr" | "downright" => Self::SouthEast,
+            "sw" | "southwest" | "dl" | "downleft" => Self::SouthWest,
+            "nw" | "northwest" | "ul" | "upleft" => Self::NorthWest,
+            _ => {
+                return Err(ParseDirectionError(s.to_string()));
+            }
+        })
+    }
+}
+
+impl Direction8 {
+    pub const VALUES: [Self; 8] = [
+        Self::North,
+        Self::NorthEast,
+        Self::East,
+        Self::SouthEast,
+        Self::South,
+        Self::SouthWest,
+        Self::West,
+        Self::NorthWest,
+    ];
+
+    pub fn is_diagonal(&self) -> bool {
+        matches!(
+            self,
+            Self::NorthEast | Self::SouthEast | Self::SouthWest | Self::NorthWest
+        )
+    }
+
+    pub fn opposite(&self) -> Self {
+        self.rotate(4)
+    }
+
+    /// Rotates clockwise by `steps` eighth turns, negative values rotate counter-clockwise.
+    pub fn rotate(&self, steps: i64) -> Self {
+        let index = Self::VALUES.iter().position(|d| d == self).unwrap() as i64;
+        Self::VALUES[(index + steps).rem_euclid(Self::VALUES.len() as i64) as usize]
+    }
+
+    pub fn rotate_ccw(&self) -> Self {
+        self.rotate(-1)
+    }
+
+    pub fn rotate_cw(&self) -> Self {
+        self.rotate(1)
+    }
+
+    pub fn vec(&self) -> Vec2i {
+        match self {
+            Self::North => Vec2i::new(0, -1),
+            Self::NorthEast => Vec2i::new(1, -1),
+            Self::East => Vec2i::new(1, 0),
+            Self::SouthEast => Vec2i::new(1, 1),
+            Self::South => Vec2i::new(0, 1),
+            Self::SouthWest => Vec2i::new(-1, 1),
+            Self::West => Vec2i::new(-1, 0),
+            Self::NorthWest => Vec2i::new(-1, -1),
+        }
+    }
+
+    pub fn offset(&self, pos: &Vec2i) -> Vec2i {
+        self.offset_with_amount(pos, 1)
+    }
+
+    pub fn offset_with_amount(&self, pos: &Vec2i, amount: i64) -> Vec2i {
+        pos + amount * self.vec()
+    }
+}
+
+/// The six directions on a hex grid with flat-topped hexagons.
+///
+/// Positions use axial coordinates `(q, r)` with north pointing towards negative `r`, the
+/// matching cube coordinates are `(q, r, -q - r)`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum HexDirection {
+    North,
+    NorthEast,
+    SouthEast,
+    South,
+    SouthWest,
+    NorthWest,
+}
+
+impl From<HexDirection> for Direction8 {
+    fn from(value: HexDirection) -> Self {
+        match value {
+            HexDirection::North => Self::North,
+            HexDirection::NorthEast => Self::NorthEast,
+            HexDirection::SouthEast => Self::SouthEast,
+            HexDirection::South => Self::South,
+            HexDirection::SouthWest => Self::SouthWest,
+            HexDirection::NorthWest => Self::NorthWest,
+        }
+    }
+}
+
+impl TryFrom<Direction8> for HexDirection {
+    type Error = ();
+
+    fn try_from(value: Direction8) -> Result<Self, Self::Error> {
+        Ok(match value {
+            Direction8::North => Self::North,
+            Direction8::NorthEast => Self::NorthEast,
+            Direction8::SouthEast => Self::SouthEast,
+            Direction8::South => Self::South,
+            Direction8::SouthWest => Self::SouthWest,
+            Direction8::NorthWest => Self::NorthWest,
+            _ => {
+                return Err(());
+            }
+        })
+    }
+}
+
+impl FromStr for HexDirection {
+    type Err = ParseDirectionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse::<Direction8>()
+            .ok()
+            .and_then(|d| d.try_into().ok())
+            .ok_or_else(|| ParseDirectionError(s.to_string()))
+    }
+}
+
+impl HexDirection {
+    pub const VALUES: [Self; 6] = [
+        Self::North,
+        Self::NorthEast,
+        Self::SouthEast,
+        Self::South,
+        Self::SouthWest,
+        Self::NorthWest,
+    ];
+
+    pub fn opposite(&self) -> Self {
+        self.rotate(3)
+    }
+
+    /// Rotates clockwise by `steps` sixth turns, negative values rotate counter-clockwise.
+    pub fn rotate(&self, steps: i64) -> Self {
+        let index = Self::VALUES.iter().position(|d| d == self).unwrap() as i64;
+        Self::VALUES[(index + steps).rem_euclid(Self::VALUES.len() as i64) as usize]
+    }
+
+    pub fn rotate_ccw(&self) -> Self {
+        self.rotate(-1)
+    }
+
+    pub fn rotate_cw(&self) -> Self {
+        self.rotate(1)
+    }
+
+    /// The axial offset `(q, r)`.
+    pub fn vec(&self) -> Vec2i {
+        match self {
+            Self::North => Vec2i::new(0, -1),
+            Self::NorthEast => Vec2i::new(1, -1),
+            Self::SouthEast => Vec2i::new(1, 0),
+            Self::South => Vec2i::new(0, 1),
+            Self::SouthWest => Vec2i::new(-1, 1),
+            Self::NorthWest => Vec2i::new(-1, 0),
+        }
+    }
+
+    /// The cube offset `(q, r, s)`.
+    pub fn cube_vec(&self) -> Vec3i {
+        axial_to_cube(&self.vec())
+    }
+
+    pub fn offset(&self, pos: &Vec2i) -> Vec2i {
+        self.offset_with_amount(pos, 1)
+    }
+
+    pub fn offset_with_amount(&self, pos: &Vec2i, amount: i64) -> Vec2i {
+        pos + amount * self.vec()
+    }
+}
+
+pub fn axial_to_cube(pos: &Vec2i) -> Vec3i {
+    Vec3i::new(pos.x, pos.y, -pos.x - pos.y)
+}
+
+pub fn cube_to_axial(pos: &Vec3i) -> Vec2i {
+    Vec2i::new(pos.x, pos.y)
+}
+
+/// Number of steps between two hexes given in axial coordinates.
+pub fn hex_distance(a: &Vec2i, b: &Vec2i) -> i64 {
+    let d = axial_to_cube(&(b - a));
+    (d.x.abs() + d.y.abs() + d.z.abs()) / 2
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("north".parse(), Ok(Direction::North));
+        assert_eq!("Up".parse(), Ok(Direction::North));
+        assert_eq!("NE".parse(), Ok(Direction8::NorthEast));
+        assert_eq!("south-west".parse(), Ok(Direction8::SouthWest));
+        assert_eq!("left".parse(), Ok(Direction8::West));
+        assert_eq!("nw".parse(), Ok(HexDirection::NorthWest));
+        assert!("east".parse::<HexDirection>().is_err());
+        assert!("sideways".parse::<Direction>().is_err());
+    }
+
+    #[test]
+    fn test_rotate() {
+        assert_eq!(Direction::North.rotate(1), Direction::East);
+        assert_eq!(Direction::North.rotate(-5), Direction::West);
+        assert_eq!(Direction8::North.rotate(3), Direction8::SouthEast);
+        assert_eq!(Direction8::East.rotate_ccw(), Direction8::NorthEast);
+        assert_eq!(Direction8::NorthWest.opposite(), Direction8::SouthEast);
+        assert_eq!(HexDirection::North.rotate(-1), HexDirection::NorthWest);
+        assert_eq!(HexDirection::NorthEast.opposite(), HexDirection::SouthWest);
+    }
+
+    #[test]
+    fn test_vec() {
+        for d in Direction8::VALUES {
+            assert_eq!(d.vec() + d.opposite().vec(), Vec2i::zeros());
+        }
+        for d in HexDirection::VALUES {
+            assert_eq!(d.vec() + d.opposite().vec(), Vec2i::zeros());
+            assert_eq!(d.cube_vec().sum(), 0);
+            assert_eq!(hex_distance(&Vec2i::zeros(), &d.vec()), 1);
+        }
+        assert_eq!(
+            Direction8::SouthWest.offset_with_amount(&Vec2i::new(1, 1), 3),
+            Vec2i::new(-2, 4)
+        );
+    }
+
+    #[test]
+    fn test_hex_distance() {
+        let pos = [
+            HexDirection::NorthEast,
+            HexDirection::NorthEast,
+            HexDirection::South,
+            HexDirection::South,
+        ]
+        .iter()
+        .fold(Vec2i::zeros(), |p, d| d.offset(&p));
+        assert_eq!(hex_distance(&Vec2i::zeros(), &pos), 2);
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(Direction8::from(Direction::West), Direction8::West);
+        assert_eq!(Direction::try_from(Direction8::NorthEast), Err(()));
+        assert_eq!(
+            HexDirection::try_from(Direction8::from(HexDirection::South)),
+            Ok(HexDirection::South)
+        );
+    }
+}
@@ -0,0 +1,346 @@
+//! Exact 2-D geometry on integer and rational vectors.
+//!
+//! Predicates only ever multiply and subtract coordinates, constructions that need division
+//! (like intersection points) take [`Ratio`] coordinates, so no result suffers from rounding.
+
+use std::cmp::Ordering;
+use std::fmt::Debug;
+
+use nalgebra::{Scalar, Vector2};
+use num::rational::Ratio;
+use num::{Integer, Signed, Zero};
+
+/// Cross product of `a - o` and `b - o`.
+///
+/// Positive if `o -> a -> b` turns counter-clockwise in a y-up coordinate system.
+pub fn cross<T: Scalar + Copy + Signed>(o: &Vector2<T>, a: &Vector2<T>, b: &Vector2<T>) -> T {
+    (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+}
+
+fn dot<T: Scalar + Copy + Signed>(o: &Vector2<T>, a: &Vector2<T>, b: &Vector2<T>) -> T {
+    (a.x - o.x) * (b.x - o.x) + (a.y - o.y) * (b.y - o.y)
+}
+
+/// Cross product of `a2 - a1` and `b2 - b1`.
+fn direction_cross<T: Scalar + Copy + Signed>(
+    a1: &Vector2<T>,
+    a2: &Vector2<T>,
+    b1: &Vector2<T>,
+    b2: &Vector2<T>,
+) -> T {
+    (a2.x - a1.x) * (b2.y - b1.y) - (a2.y - a1.y) * (b2.x - b1.x)
+}
+
+/// Whether `p` lies on the closed segment from `a` to `b`.
+pub fn on_segment<T: Scalar + Copy + Signed + PartialOrd>(
+    p: &Vector2<T>,
+    a: &Vector2<T>,
+    b: &Vector2<T>,
+) -> bool {
+    cross(a, b, p).is_zero() && !dot(p, a, b).is_positive()
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SegmentIntersection<T: Scalar> {
+    Point(Vector2<T>),
+    /// The segments are collinear and share the segment between the two points.
+    Overlap(Vector2<T>, Vector2<T>),
+}
+
+fn lerp<I: Integer + Signed + Copy + Debug + 'static>(
+    a: &Vector2<Ratio<I>>,
+    b: &Vector2<Ratio<I>>,
+    t: Ratio<I>,
+) -> Vector2<Ratio<I>> {
+    Vector2::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t)
+}
+
+/// Intersection of the closed segments `p1 -> p2` and `q1 -> q2`.
+pub fn segment_intersection<I: Integer + Signed + Copy + Debug + 'static>(
+    p1: &Vector2<Ratio<I>>,
+    p2: &Vector2<Ratio<I>>,
+    q1: &Vector2<Ratio<I>>,
+    q2: &Vector2<Ratio<I>>,
+) -> Option<SegmentIntersection<Ratio<I>>> {
+    if p1 == p2 {
+        return on_segment(p1, q1, q2).then_some(SegmentIntersection::Point(*p1));
+    }
+    if q1 == q2 {
+        return on_segment(q1, p1, p2).then_some(SegmentIntersection::Point(*q1));
+    }
+
+    let denominator = direction_cross(p1, p2, q1, q2);
+    let zero = Ratio::from_integer(I::zero());
+    let one = Ratio::from_integer(I::one());
+    if denominator.is_zero() {
+        if !cross(p1, p2, q1).is_zero() {
+            // parallel
+            return None;
+        }
+
+        // collinear: project q onto p1 -> p2, where p1 is at 0 and p2 at 1
+        let rr = dot(p1, p2, p2);
+        let t0 = dot(p1, p2, q1) / rr;
+        let t1 = dot(p1, p2, q2) / rr;
+        let (t0, t1) = if t0 <= t1 { (t0, t1) } else { (t1, t0) };
+        let (lo, hi) = (t0.max(zero), t1.min(one));
+        return match lo.cmp(&hi) {
+            Ordering::Less => Some(SegmentIntersection::Overlap(
+                lerp(p1, p2, lo),
+                lerp(p1, p2, hi),
+            )),
+            Ordering::Equal => Some(SegmentIntersection::Point(lerp(p1, p2, lo))),
+            Ordering::Greater => None,
+        };
+    }
+
+    let t = direction_cross(p1, q1, q1, q2) / denominator;
+    let u = direction_cross(p1, q1, p1, p2) / denominator;
+    (zero <= t && t <= one && zero <= u && u <= one)
+        .then(|| SegmentIntersection::Point(lerp(p1, p2, t)))
+}
+
+/// Intersection point of the infinite lines through `p1, p2` and `q1, q2`, `None` if they are
+/// parallel or identical.
+pub fn line_intersection<I: Integer + Signed + Copy + Debug + 'static>(
+    p1: &Vector2<Ratio<I>>,
+    p2: &Vector2<Ratio<I>>,
+    q1: &Vector2<Ratio<I>>,
+    q2: &Vector2<Ratio<I>>,
+) -> Option<Vector2<Ratio<I>>> {
+    let denominator = direction_cross(p1, p2, q1, q2);
+    if denominator.is_zero() {
+        return None;
+    }
+
+    Some(lerp(p1, p2, direction_cross(p1, q1, q1, q2) / denominator))
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Containment {
+    Inside,
+    Boundary,
+    Outside,
+}
+
+/// Locates `p` relative to the simple polygon with the given vertices.
+pub fn point_in_polygon<T: Scalar + Copy + Signed + PartialOrd>(
+    p: &Vector2<T>,
+    polygon: &[Vector2<T>],
+) -> Containment {
+    let mut inside = false;
+    for (i, a) in polygon.iter().enumerate() {
+        let b = &polygon[(i + 1) % polygon.len()];
+        if on_segment(p, a, b) {
+            return Containment::Boundary;
+        }
+
+        // count crossings of the ray from p towards positive x
+        if (a.y > p.y) != (b.y > p.y) {
+            let c = cross(a, b, p);
+            if (b.y > a.y && c.is_positive()) || (b.y < a.y && c.is_negative()) {
+                inside = !inside;
+            }
+        }
+    }
+
+    if inside {
+        Containment::Inside
+    } else {
+        Containment::Outside
+    }
+}
+
+/// Twice the signed area of the polygon (shoelace formula), positive for counter-clockwise
+/// vertices in a y-up coordinate system.
+pub fn double_signed_area<T: Scalar + Copy + Signed>(polygon: &[Vector2<T>]) -> T {
+    polygon.iter().enumerate().fold(T::zero(), |acc, (i, a)| {
+        let b = &polygon[(i + 1) % polygon.len()];
+        acc + a.x * b.y - b.x * a.y
+    })
+}
+
+/// The area of the polygon.
+pub fn polygon_area<I: Integer + Signed + Copy + Debug + 'static>(
+    polygon: &[Vector2<Ratio<I>>],
+) -> Ratio<I> {
+    double_signed_area(polygon).abs() / (I::one() + I::one())
+}
+
+/// The area of a polygon with integer vertices.
+pub fn lattice_polygon_area<I: Integer + Signed + Copy + Debug + 'static>(
+    polygon: &[Vector2<I>],
+) -> Ratio<I> {
+    Ratio::new(double_signed_area(polygon).abs(), I::one() + I::one())
+}
+
+/// Number of lattice points on the boundary of a polygon with integer vertices.
+pub fn boundary_points<I: Integer + Signed + Copy + Debug + 'static>(polygon: &[Vector2<I>]) -> I {
+    polygon.iter().enumerate().fold(I::zero(), |acc, (i, a)| {
+        let b = &polygon[(i + 1) % polygon.len()];
+        acc + (b.x - a.x).abs().gcd(&(b.y - a.y).abs())
+    })
+}
+
+/// Number of lattice points strictly inside a simple polygon with integer vertices, using Pick's
+/// theorem `A = i + b/2 - 1`.
+pub fn interior_points<I: Integer + Signed + Copy + Debug + 'static>(polygon: &[Vector2<I>]) -> I {
+    let two = I::one() + I::one();
+    (double_signed_area(polygon).abs() - boundary_points(polygon) + two) / two
+}
+
+/// The convex hull of `points` in counter-clockwise order (y-up), starting at the smallest point.
+///
+/// Collinear points on the hull's edges are left out.
+pub fn convex_hull<T: Scalar + Copy + Signed + PartialOrd>(
+    points: &[Vector2<T>],
+) -> Vec<Vector2<T>> {
+    let mut points = points.to_vec();
+    points.sort_unstable_by(|a, b| {
+        a.x.partial_cmp(&b.x)
+            .unwrap()
+            .then_with(|| a.y.partial_cmp(&b.y).unwrap())
+    });
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+
+    let mut hull: Vec<Vector2<T>> = Vec::with_capacity(2 * points.len());
+    for p in &points {
+        while hull.len() >= 2
+            && !cross(&hull[hull.len() - 2], &hull[hull.len() - 1], p).is_positive()
+        {
+            hull.pop();
+        }
+        hull.push(*p);
+    }
+    let lower_len = hull.len();
+    for p in points.iter().rev().skip(1) {
+        while hull.len() > lower_len
+            && !cross(&hull[hull.len() - 2], &hull[hull.len() - 1], p).is_positive()
+        {
+            hull.pop();
+        }
+        hull.push(*p);
+    }
+
+    // the last point is the first one again
+    hull.pop();
+    hull
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::common::{Vec2i, Vec2r};
+
+    fn r(x: i64, y: i64) -> Vec2r {
+        Vec2r::new(x.into(), y.into())
+    }
+
+    #[test]
+    fn test_segment_intersection() {
+        assert_eq!(
+            segment_intersection(&r(0, 0), &r(2, 2), &r(0, 1), &r(1, 0)),
+            Some(SegmentIntersection::Point(Vec2r::new(
+                Ratio::new(1, 2),
+                Ratio::new(1, 2)
+            )))
+        );
+        assert_eq!(
+            segment_intersection(&r(0, 0), &r(1, 1), &r(2, 0), &r(3, -1)),
+            None
+        );
+        assert_eq!(
+            segment_intersection(&r(0, 0), &r(4, 0), &r(6, 0), &r(2, 0)),
+            Some(SegmentIntersection::Overlap(r(2, 0), r(4, 0)))
+        );
+        assert_eq!(
+            segment_intersection(&r(0, 0), &r(2, 0), &r(2, 0), &r(3, 0)),
+            Some(SegmentIntersection::Point(r(2, 0)))
+        );
+        assert_eq!(
+            segment_intersection(&r(0, 0), &r(2, 0), &r(0, 1), &r(2, 1)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_line_intersection() {
+        assert_eq!(
+            line_intersection(&r(0, 0), &r(1, 3), &r(0, 1), &r(1, 1)),
+            Some(Vec2r::new(Ratio::new(1, 3), 1.into()))
+        );
+        assert_eq!(
+            line_intersection(&r(0, 0), &r(1, 1), &r(0, 1), &r(1, 2)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_point_in_polygon() {
+        let polygon = [
+            Vec2i::new(0, 0),
+            Vec2i::new(4, 0),
+            Vec2i::new(4, 4),
+            Vec2i::new(2, 2),
+            Vec2i::new(0, 4),
+        ];
+        assert_eq!(
+            point_in_polygon(&Vec2i::new(1, 1), &polygon),
+            Containment::Inside
+        );
+        assert_eq!(
+            point_in_polygon(&Vec2i::new(2, 3), &polygon),
+            Containment::Outside
+        );
+        assert_eq!(
+            point_in_polygon(&Vec2i::new(3, 3), &polygon),
+            Containment::Boundary
+        );
+        assert_eq!(
+            point_in_polygon(&Vec2i::new(5, 0), &polygon),
+            Containment::Outside
+        );
+    }
+
+    #[test]
+    fn test_area_and_pick() {
+        let polygon = [
+            Vec2i::new(0, 0),
+            Vec2i::new(4, 0),
+            Vec2i::new(4, 3),
+            Vec2i::new(0, 3),
+        ];
+        assert_eq!(lattice_polygon_area(&polygon), Ratio::from_integer(12));
+        assert_eq!(boundary_points(&polygon), 14);
+        assert_eq!(interior_points(&polygon), 6);
+        let triangle = [r(0, 0), r(1, 0), r(0, 1)];
+        assert_eq!(polygon_area(&triangle), Ratio::new(1, 2));
+    }
+
+    #[test]
+    fn test_convex_hull() {
+        let points = [
+            Vec2i::new(0, 0),
+            Vec2i::new(2, 0),
+            Vec2i::new(1, 1),
+            Vec2i::new(2, 2),
+            Vec2i::new(0, 2),
+            Vec2i::new(1, 0),
+            Vec2i::new(0, 0),
+        ];
+        assert_eq!(
+            convex_hull(&points),
+            vec![
+                Vec2i::new(0, 0),
+                Vec2i::new(2, 0),
+                Vec2i::new(2, 2),
+                Vec2i::new(0, 2)
+            ]
+        );
+    }
+}
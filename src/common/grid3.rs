@@ -0,0 +1,277 @@
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+
+use itertools::Itertools;
+
+use crate::common::{Grid, Vec3i};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+/// A three-dimensional grid, stored x-major then y then z.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid3<T> {
+    pub size_x: usize,
+    pub size_y: usize,
+    pub size_z: usize,
+    grid: Vec<T>,
+}
+
+impl<T> FromStr for Grid3<T>
+where
+    char: TryInto<T>,
+{
+    type Err = <char as TryInto<T>>::Error;
+
+    /// Parses z-layers separated by blank lines, each layer is parsed like a [`Grid`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut size = None;
+        let mut size_z = 0;
+        let mut grid = vec![];
+        for (is_empty, layer) in &s.lines().map(str::trim).chunk_by(|l| l.is_empty()) {
+            if is_empty {
+                continue;
+            }
+
+            let layer: Grid<T> = layer.collect_vec().join("\n").parse()?;
+            match size {
+                None => size = Some((layer.size_x, layer.size_y)),
+                Some(size) if size == (layer.size_x, layer.size_y) => {}
+                _ => {
+                    panic!("layers differ in size");
+                }
+            }
+            size_z += 1;
+            grid.extend(layer.grid);
+        }
+
+        let (size_x, size_y) = size.unwrap();
+        Ok(Self {
+            size_x,
+            size_y,
+            size_z,
+            grid,
+        })
+    }
+}
+
+impl<T: Clone> Grid3<T> {
+    pub fn new_from_element(size_x: usize, size_y: usize, size_z: usize, element: T) -> Self {
+        Self {
+            size_x,
+            size_y,
+            size_z,
+            grid: vec![element; size_x * size_y * size_z],
+        }
+    }
+
+    /// The 2-D grid of all cells whose coordinate along `axis` equals `index`.
+    ///
+    /// The remaining two axes keep their order, e.g. slicing along [`Axis::Y`] yields a grid
+    /// indexed by `(x, z)`. Panics if `index` is outside the grid along `axis`.
+    pub fn slice(&self, axis: Axis, index: i64) -> Grid<T> {
+        let (size_x, size_y, size) = match axis {
+            Axis::X => (self.size_y, self.size_z, self.size_x),
+            Axis::Y => (self.size_x, self.size_z, self.size_y),
+            Axis::Z => (self.size_x, self.size_y, self.size_z),
+        };
+        assert!(
+            (0..size as i64).contains(&index),
+            "slice {index} along {axis:?} is out of bounds for a {}x{}x{} grid",
+            self.size_x,
+            self.size_y,
+            self.size_z
+        );
+        let grid = (0..size_y as i64)
+            .flat_map(|b| (0..size_x as i64).map(move |a| (a, b)))
+            .map(|(a, b)| {
+                let pos = match axis {
+                    Axis::X => Vec3i::new(index, a, b),
+                    Axis::Y => Vec3i::new(a, index, b),
+                    Axis::Z => Vec3i::new(a, b, index),
+                };
+                self[pos].clone()
+            })
+            .collect();
+        Grid {
+            size_x,
+            size_y,
+            grid,
+        }
+    }
+}
+
+impl<T: Default + Clone> Grid3<T> {
+    pub fn new_from_default(size_x: usize, size_y: usize, size_z: usize) -> Self {
+        Self::new_from_element(size_x, size_y, size_z, T::default())
+    }
+}
+
+impl<T> Grid3<T> {
+    /// Offsets to the 6 face-adjacent cells.
+    pub const NEIGHBORS_6: [Vec3i; 6] = [
+        Vec3i::new(1, 0, 0),
+        Vec3i::new(-1, 0, 0),
+        Vec3i::new(0, 1, 0),
+        Vec3i::new(0, -1, 0),
+        Vec3i::new(0, 0, 1),
+        Vec3i::new(0, 0, -1),
+    ];
+
+    pub fn in_bounds(&self, pos: &Vec3i) -> bool {
+        pos.x >= 0
+            && (pos.x as usize) < self.size_x
+            && pos.y >= 0
+            && (pos.y as usize) < self.size_y
+            && pos.z >= 0
+            && (pos.z as usize) < self.size_z
+    }
+
+    /// The in-bounds positions sharing a face with `pos`.
+    pub fn neighbors6(&self, pos: &Vec3i) -> impl Iterator<Item = Vec3i> + '_ {
+        let pos = *pos;
+        Self::NEIGHBORS_6
+            .into_iter()
+            .map(move |d| pos + d)
+            .filter(|p| self.in_bounds(p))
+    }
+
+    /// The in-bounds positions sharing a face, edge or corner with `pos`.
+    pub fn neighbors26(&self, pos: &Vec3i) -> impl Iterator<Item = Vec3i> + '_ {
+        let pos = *pos;
+        itertools::iproduct!(-1..=1, -1..=1, -1..=1)
+            .filter(|&d| d != (0, 0, 0))
+            .map(move |(x, y, z)| pos + Vec3i::new(x, y, z))
+            .filter(|p| self.in_bounds(p))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.grid.iter()
+    }
+
+    pub fn pos_iter(&self) -> impl Iterator<Item = (Vec3i, &T)> {
+        let layer = self.size_x * self.size_y;
+        self.grid.iter().enumerate().map(move |(i, t)| {
+            (
+                Vec3i::new(
+                    (i % self.size_x) as _,
+                    ((i % layer) / self.size_x) as _,
+                    (i / layer) as _,
+                ),
+                t,
+            )
+        })
+    }
+
+    fn index_of(&self, pos: &Vec3i) -> Option<usize> {
+        self.in_bounds(pos).then(|| {
+            (pos.x as usize) + self.size_x * ((pos.y as usize) + self.size_y * (pos.z as usize))
+        })
+    }
+
+    fn out_of_bounds(&self, pos: &Vec3i) -> ! {
+        panic!(
+            "position ({}, {}, {}) is out of bounds for a {}x{}x{} grid",
+            pos.x, pos.y, pos.z, self.size_x, self.size_y, self.size_z
+        )
+    }
+
+    pub fn get(&self, pos: &Vec3i) -> Option<&T> {
+        self.index_of(pos).map(|i| &self.grid[i])
+    }
+
+    pub fn get_mut(&mut self, pos: &Vec3i) -> Option<&mut T> {
+        self.index_of(pos).map(|i| &mut self.grid[i])
+    }
+}
+
+impl<T> Index<Vec3i> for Grid3<T> {
+    type Output = T;
+
+    fn index(&self, index: Vec3i) -> &Self::Output {
+        match self.index_of(&index) {
+            Some(i) => &self.grid[i],
+            None => self.out_of_bounds(&index),
+        }
+    }
+}
+
+impl<T> IndexMut<Vec3i> for Grid3<T> {
+    fn index_mut(&mut self, index: Vec3i) -> &mut Self::Output {
+        match self.index_of(&index) {
+            Some(i) => &mut self.grid[i],
+            None => self.out_of_bounds(&index),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const INPUT: &str = r#"ab
+cd
+
+ef
+gh
+
+ij
+kl"#;
+
+    #[test]
+    fn test_parse() {
+        let grid: Grid3<char> = INPUT.parse().unwrap();
+        assert_eq!((grid.size_x, grid.size_y, grid.size_z), (2, 2, 3));
+        assert_eq!(grid[Vec3i::new(1, 0, 1)], 'f');
+        assert_eq!(grid[Vec3i::new(0, 1, 2)], 'k');
+        assert_eq!(grid.pos_iter().nth(6), Some((Vec3i::new(0, 1, 1), &'g')));
+    }
+
+    #[test]
+    fn test_slice() {
+        let grid: Grid3<char> = INPUT.parse().unwrap();
+        assert_eq!(grid.slice(Axis::Z, 1), "ef\ngh".parse().unwrap());
+        assert_eq!(grid.slice(Axis::Y, 0), "ab\nef\nij".parse().unwrap());
+        assert_eq!(grid.slice(Axis::X, 1), "bd\nfh\njl".parse().unwrap());
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid: Grid3<u8> = Grid3::new_from_default(3, 3, 3);
+        assert_eq!(grid.neighbors6(&Vec3i::new(1, 1, 1)).count(), 6);
+        assert_eq!(grid.neighbors26(&Vec3i::new(1, 1, 1)).count(), 26);
+        assert_eq!(grid.neighbors6(&Vec3i::new(0, 0, 0)).count(), 3);
+        assert_eq!(grid.neighbors26(&Vec3i::new(0, 0, 0)).count(), 7);
+        assert!(!grid.in_bounds(&Vec3i::new(0, 3, 0)));
+    }
+
+    #[test]
+    fn test_checked_access() {
+        let mut grid: Grid3<char> = INPUT.parse().unwrap();
+        assert_eq!(grid.get(&Vec3i::new(1, 1, 2)), Some(&'l'));
+        assert_eq!(grid.get(&Vec3i::new(2, 0, 0)), None);
+        assert_eq!(grid.get(&Vec3i::new(0, 0, -1)), None);
+        *grid.get_mut(&Vec3i::new(0, 0, 0)).unwrap() = 'z';
+        assert_eq!(grid[Vec3i::new(0, 0, 0)], 'z');
+    }
+
+    #[test]
+    #[should_panic(expected = "position (2, 0, 0) is out of bounds for a 2x2x3 grid")]
+    fn test_index_past_row_end() {
+        let grid: Grid3<char> = INPUT.parse().unwrap();
+        let _ = grid[Vec3i::new(2, 0, 0)];
+    }
+
+    #[test]
+    #[should_panic(expected = "slice 3 along Z is out of bounds for a 2x2x3 grid")]
+    fn test_slice_out_of_bounds() {
+        let grid: Grid3<char> = INPUT.parse().unwrap();
+        grid.slice(Axis::Z, 3);
+    }
+}
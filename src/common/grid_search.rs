@@ -0,0 +1,224 @@
+use std::collections::VecDeque;
+
+use num::Zero;
+use pathfinding::prelude::{astar, bfs, bfs_reach, dijkstra, dijkstra_all};
+use rustc_hash::FxHashSet;
+
+use crate::common::{Direction, Grid, Vec2i};
+
+impl<T> Grid<T> {
+    /// The in-bounds positions orthogonally adjacent to `pos`.
+    pub fn neighbors(&self, pos: &Vec2i) -> impl Iterator<Item = Vec2i> + '_ {
+        let pos = *pos;
+        Direction::VALUES
+            .into_iter()
+            .map(move |d| d.offset(&pos))
+            .filter(|p| self.in_bounds(p))
+    }
+
+    fn passable_neighbors<'a>(
+        &'a self,
+        pos: &Vec2i,
+        passable: &'a impl Fn(&T) -> bool,
+    ) -> impl Iterator<Item = Vec2i> + 'a {
+        self.neighbors(pos).filter(|&p| passable(&self[p]))
+    }
+
+    fn weighted_neighbors<'a, C>(
+        &'a self,
+        pos: &Vec2i,
+        cost: &'a impl Fn(&T) -> Option<C>,
+    ) -> impl Iterator<Item = (Vec2i, C)> + 'a {
+        self.neighbors(pos)
+            .filter_map(|p| cost(&self[p]).map(|c| (p, c)))
+    }
+
+    /// Shortest path from `start` to `goal` moving only onto tiles for which `passable` holds.
+    ///
+    /// The returned path includes both `start` and `goal`.
+    pub fn bfs(
+        &self,
+        start: &Vec2i,
+        goal: &Vec2i,
+        passable: impl Fn(&T) -> bool,
+    ) -> Option<Vec<Vec2i>> {
+        bfs(
+            start,
+            |p| self.passable_neighbors(p, &passable),
+            |p| p == goal,
+        )
+    }
+
+    /// Number of steps needed to reach every tile from `start`, `None` for unreachable tiles.
+    pub fn bfs_distances(
+        &self,
+        start: &Vec2i,
+        passable: impl Fn(&T) -> bool,
+    ) -> Grid<Option<usize>> {
+        let mut distances = Grid::new_from_element(self.size_x, self.size_y, None);
+        distances[*start] = Some(0);
+        let mut queue = VecDeque::from([(*start, 0)]);
+        while let Some((pos, distance)) = queue.pop_front() {
+            for next in self.passable_neighbors(&pos, &passable) {
+                if distances[next].is_none() {
+                    distances[next] = Some(distance + 1);
+                    queue.push_back((next, distance + 1));
+                }
+            }
+        }
+
+        distances
+    }
+
+    /// Cheapest path from `start` to `goal` where `cost` gives the cost of entering a tile or
+    /// `None` if the tile cannot be entered.
+    pub fn dijkstra<C: Zero + Ord + Copy>(
+        &self,
+        start: &Vec2i,
+        goal: &Vec2i,
+        cost: impl Fn(&T) -> Option<C>,
+    ) -> Option<(Vec<Vec2i>, C)> {
+        dijkstra(start, |p| self.weighted_neighbors(p, &cost), |p| p == goal)
+    }
+
+    /// Cheapest cost of reaching every tile from `start`, `None` for unreachable tiles.
+    pub fn dijkstra_distances<C: Zero + Ord + Copy>(
+        &self,
+        start: &Vec2i,
+        cost: impl Fn(&T) -> Option<C>,
+    ) -> Grid<Option<C>> {
+        let mut distances = Grid::new_from_element(self.size_x, self.size_y, None);
+        for (pos, (_, c)) in dijkstra_all(start, |p| self.weighted_neighbors(p, &cost)) {
+            distances[pos] = Some(c);
+        }
+        distances[*start] = Some(C::zero());
+        distances
+    }
+
+    /// Like [`Grid::dijkstra`], guided by `heuristic`.
+    ///
+    /// The heuristic must never overestimate the remaining cost, e.g. the manhattan distance to
+    /// `goal` if entering a tile costs at least 1.
+    pub fn astar<C: Zero + Ord + Copy>(
+        &self,
+        start: &Vec2i,
+        goal: &Vec2i,
+        cost: impl Fn(&T) -> Option<C>,
+        heuristic: impl Fn(&Vec2i) -> C,
+    ) -> Option<(Vec<Vec2i>, C)> {
+        astar(
+            start,
+            |p| self.weighted_neighbors(p, &cost),
+            heuristic,
+            |p| p == goal,
+        )
+    }
+
+    /// All tiles reachable from `start`, including `start` itself.
+    pub fn flood_fill(&self, start: &Vec2i, passable: impl Fn(&T) -> bool) -> FxHashSet<Vec2i> {
+        bfs_reach(*start, |p| self.passable_neighbors(p, &passable)).collect()
+    }
+
+    /// Labels every tile with the index of its connected component.
+    ///
+    /// Two adjacent tiles belong to the same component if `connected` holds for them.
+    /// Returns the labels together with the number of components.
+    pub fn connected_components(&self, connected: impl Fn(&T, &T) -> bool) -> (Grid<usize>, usize) {
+        let mut labels: Grid<Option<usize>> =
+            Grid::new_from_element(self.size_x, self.size_y, None);
+        let mut count = 0;
+        for (pos, _) in self.pos_iter() {
+            if labels[pos].is_some() {
+                continue;
+            }
+
+            for p in bfs_reach(pos, |p| {
+                let t = &self[*p];
+                self.neighbors(p)
+                    .filter(|&n| connected(t, &self[n]))
+                    .collect::<Vec<_>>()
+            }) {
+                labels[p] = Some(count);
+            }
+            count += 1;
+        }
+
+        let labels = Grid {
+            size_x: self.size_x,
+            size_y: self.size_y,
+            grid: labels.grid.into_iter().map(Option::unwrap).collect(),
+        };
+        (labels, count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::common::manhattan_distance;
+
+    const MAZE: &str = r#"S..#
+.#.#
+.#..
+...E"#;
+
+    fn maze() -> Grid<char> {
+        MAZE.parse().unwrap()
+    }
+
+    #[test]
+    fn test_bfs() {
+        let path = maze()
+            .bfs(&Vec2i::new(0, 0), &Vec2i::new(3, 3), |&c| c != '#')
+            .unwrap();
+        assert_eq!(path.len(), 7);
+        assert_eq!(path[0], Vec2i::new(0, 0));
+        assert_eq!(path[6], Vec2i::new(3, 3));
+    }
+
+    #[test]
+    fn test_bfs_distances() {
+        let distances = maze().bfs_distances(&Vec2i::new(0, 0), |&c| c != '#');
+        assert_eq!(distances[Vec2i::new(3, 3)], Some(6));
+        assert_eq!(distances[Vec2i::new(3, 0)], None);
+        assert_eq!(distances[Vec2i::new(2, 2)], Some(4));
+    }
+
+    #[test]
+    fn test_dijkstra_astar() {
+        let grid: Grid<char> = "131\n191\n111".parse().unwrap();
+        let cost = |c: &char| c.to_digit(10).map(u64::from);
+        let start = Vec2i::new(0, 0);
+        let goal = Vec2i::new(2, 2);
+        let (path, c) = grid.dijkstra(&start, &goal, cost).unwrap();
+        assert_eq!(c, 4);
+        assert_eq!(path.len(), 5);
+        let (_, c) = grid
+            .astar(&start, &goal, cost, |p| manhattan_distance(&goal, p) as u64)
+            .unwrap();
+        assert_eq!(c, 4);
+        let distances = grid.dijkstra_distances(&start, cost);
+        assert_eq!(distances[start], Some(0));
+        assert_eq!(distances[Vec2i::new(1, 1)], Some(10));
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        assert_eq!(
+            maze().flood_fill(&Vec2i::new(0, 0), |&c| c != '#').len(),
+            12
+        );
+    }
+
+    #[test]
+    fn test_connected_components() {
+        let grid: Grid<char> = "AAB\nBAB\nBBA".parse().unwrap();
+        let (labels, count) = grid.connected_components(|a, b| a == b);
+        assert_eq!(count, 4);
+        assert_eq!(labels[Vec2i::new(0, 0)], labels[Vec2i::new(1, 1)]);
+        assert_eq!(labels[Vec2i::new(0, 1)], labels[Vec2i::new(1, 2)]);
+        assert_ne!(labels[Vec2i::new(2, 0)], labels[Vec2i::new(0, 1)]);
+    }
+}
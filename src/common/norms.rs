@@ -0,0 +1,187 @@
+use nalgebra::{Dim, Matrix, SVector, Scalar, Storage};
+use num::{Signed, ToPrimitive};
+use thiserror::Error;
+
+#[derive(Error, Debug, Copy, Clone, Eq, PartialEq)]
+pub enum NormError {
+    #[error("expected a vector, got a {0}x{1} matrix")]
+    NotAVector(usize, usize),
+    #[error("the exponent of an lp norm must be at least 1")]
+    InvalidExponent,
+}
+
+fn check_vector<T: Scalar, R: Dim, C: Dim, S: Storage<T, R, C>>(
+    v: &Matrix<T, R, C, S>,
+) -> Result<(), NormError> {
+    match v.shape() {
+        (1, _) | (_, 1) => Ok(()),
+        (r, c) => Err(NormError::NotAVector(r, c)),
+    }
+}
+
+fn max<T: PartialOrd>(a: T, b: T) -> T {
+    if b > a {
+        b
+    } else {
+        a
+    }
+}
+
+pub fn lp1_norm<T: Scalar + Signed, R: Dim, C: Dim, S: Storage<T, R, C>>(
+    v: &Matrix<T, R, C, S>,
+) -> Result<T, NormError> {
+    check_vector(v)?;
+    Ok(v.fold(T::zero(), |a, e| a + e.abs()))
+}
+
+/// The Chebyshev norm, i.e. the largest absolute component.
+pub fn linf_norm<T: Scalar + Signed + PartialOrd, R: Dim, C: Dim, S: Storage<T, R, C>>(
+    v: &Matrix<T, R, C, S>,
+) -> Result<T, NormError> {
+    check_vector(v)?;
+    Ok(v.fold(T::zero(), |a, e| max(a, e.abs())))
+}
+
+/// The square of the euclidean norm, which stays exact for integers and rationals.
+pub fn squared_l2_norm<T: Scalar + Signed, R: Dim, C: Dim, S: Storage<T, R, C>>(
+    v: &Matrix<T, R, C, S>,
+) -> Result<T, NormError> {
+    check_vector(v)?;
+    Ok(v.fold(T::zero(), |a, e| a + e.clone() * e))
+}
+
+/// The `p`-th power of the lp norm, i.e. the sum of `|x|^p` over all components.
+pub fn lp_norm_pow<T: Scalar + Signed, R: Dim, C: Dim, S: Storage<T, R, C>>(
+    v: &Matrix<T, R, C, S>,
+    p: u32,
+) -> Result<T, NormError> {
+    check_vector(v)?;
+    if p == 0 {
+        return Err(NormError::InvalidExponent);
+    }
+
+    Ok(v.fold(T::zero(), |a, e| a + num::pow(e.abs(), p as usize)))
+}
+
+/// The lp norm for any real `p >= 1`, `p = f64::INFINITY` gives the Chebyshev norm.
+pub fn lp_norm<T: Scalar + Signed + ToPrimitive, R: Dim, C: Dim, S: Storage<T, R, C>>(
+    v: &Matrix<T, R, C, S>,
+    p: f64,
+) -> Result<f64, NormError> {
+    check_vector(v)?;
+    if p.is_nan() || p < 1.0 {
+        return Err(NormError::InvalidExponent);
+    }
+
+    let components = v.iter().map(|e| e.abs().to_f64().unwrap());
+    Ok(if p.is_infinite() {
+        components.fold(0.0, f64::max)
+    } else {
+        components.map(|e| e.powf(p)).sum::<f64>().powf(p.recip())
+    })
+}
+
+fn component_distances<'a, T: Scalar + Signed, const D: usize>(
+    a: &'a SVector<T, D>,
+    b: &'a SVector<T, D>,
+) -> impl Iterator<Item = T> + 'a {
+    a.iter()
+        .zip(b.iter())
+        .map(|(a, b)| (a.clone() - b.clone()).abs())
+}
+
+pub fn manhattan_distance<T: Scalar + Signed, const D: usize>(
+    a: &SVector<T, D>,
+    b: &SVector<T, D>,
+) -> T {
+    component_distances(a, b).fold(T::zero(), |acc, d| acc + d)
+}
+
+pub fn chebyshev_distance<T: Scalar + Signed + PartialOrd, const D: usize>(
+    a: &SVector<T, D>,
+    b: &SVector<T, D>,
+) -> T {
+    component_distances(a, b).fold(T::zero(), max)
+}
+
+pub fn squared_euclidean_distance<T: Scalar + Signed, const D: usize>(
+    a: &SVector<T, D>,
+    b: &SVector<T, D>,
+) -> T {
+    component_distances(a, b).fold(T::zero(), |acc, d| acc + d.clone() * d)
+}
+
+/// The matrix norm induced by the l1 norm, i.e. the largest absolute column sum.
+pub fn induced_l1_norm<T: Scalar + Signed + PartialOrd, R: Dim, C: Dim, S: Storage<T, R, C>>(
+    m: &Matrix<T, R, C, S>,
+) -> T {
+    m.column_iter()
+        .map(|c| c.fold(T::zero(), |a, e| a + e.abs()))
+        .fold(T::zero(), max)
+}
+
+/// The matrix norm induced by the Chebyshev norm, i.e. the largest absolute row sum.
+pub fn induced_linf_norm<T: Scalar + Signed + PartialOrd, R: Dim, C: Dim, S: Storage<T, R, C>>(
+    m: &Matrix<T, R, C, S>,
+) -> T {
+    m.row_iter()
+        .map(|r| r.fold(T::zero(), |a, e| a + e.abs()))
+        .fold(T::zero(), max)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::common::{Mat2i, Mat2r, Vec2i, Vec3i};
+    use num::Rational64;
+
+    #[test]
+    fn test_vector_norms() {
+        let v = Vec3i::new(3, -4, 1);
+        assert_eq!(lp1_norm(&v), Ok(8));
+        assert_eq!(linf_norm(&v), Ok(4));
+        assert_eq!(squared_l2_norm(&v), Ok(26));
+        assert_eq!(lp_norm_pow(&v, 3), Ok(27 + 64 + 1));
+        assert_eq!(lp_norm(&Vec2i::new(3, -4), 2.0), Ok(5.0));
+        assert_eq!(lp_norm(&v, f64::INFINITY), Ok(4.0));
+        assert_eq!(lp_norm(&v, 0.5), Err(NormError::InvalidExponent));
+    }
+
+    #[test]
+    fn test_not_a_vector() {
+        let m = Mat2i::new(1, 2, 3, 4);
+        assert_eq!(lp1_norm(&m), Err(NormError::NotAVector(2, 2)));
+        assert_eq!(linf_norm(&m), Err(NormError::NotAVector(2, 2)));
+        assert_eq!(squared_l2_norm(&m), Err(NormError::NotAVector(2, 2)));
+    }
+
+    #[test]
+    fn test_distances() {
+        let a = Vec2i::new(1, 5);
+        let b = Vec2i::new(4, 1);
+        assert_eq!(manhattan_distance(&a, &b), 7);
+        assert_eq!(chebyshev_distance(&a, &b), 4);
+        assert_eq!(squared_euclidean_distance(&a, &b), 25);
+        assert_eq!(
+            manhattan_distance(&Vec3i::new(1, 1, 1), &Vec3i::new(-1, -1, -1)),
+            6
+        );
+    }
+
+    #[test]
+    fn test_matrix_norms() {
+        let m = Mat2i::new(1, -7, -2, 3);
+        assert_eq!(induced_l1_norm(&m), 10);
+        assert_eq!(induced_linf_norm(&m), 8);
+        let m = Mat2r::new(
+            Rational64::new(1, 2),
+            Rational64::new(-1, 3),
+            Rational64::new(1, 4),
+            Rational64::new(0, 1),
+        );
+        assert_eq!(induced_l1_norm(&m), Rational64::new(3, 4));
+        assert_eq!(induced_linf_norm(&m), Rational64::new(5, 6));
+    }
+}
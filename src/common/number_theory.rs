@@ -0,0 +1,219 @@
+//! Arithmetic helpers over the `num` integer traits.
+//!
+//! Functions working modulo `m` compute products of two residues, so `(m - 1)^2` has to fit
+//! into the integer type.
+
+use num::{Integer, PrimInt, Signed};
+
+/// The prime factorization of `n` as `(prime, exponent)` pairs in ascending order.
+pub fn factorize<T: PrimInt>(n: T) -> Vec<(T, u32)> {
+    assert!(n > T::zero(), "can only factorize positive numbers");
+    let mut n = n;
+    let mut factors = vec![];
+    let mut p = T::one() + T::one();
+    while p <= n / p {
+        let mut exponent = 0;
+        while (n % p).is_zero() {
+            n = n / p;
+            exponent += 1;
+        }
+        if exponent > 0 {
+            factors.push((p, exponent));
+        }
+        p = p + T::one();
+    }
+    if n > T::one() {
+        factors.push((n, 1));
+    }
+    factors
+}
+
+/// All positive divisors of `n` in ascending order.
+pub fn divisors<T: PrimInt>(n: T) -> Vec<T> {
+    let mut divisors = vec![T::one()];
+    for (p, exponent) in factorize(n) {
+        let len = divisors.len();
+        let mut power = T::one();
+        for _ in 0..exponent {
+            power = power * p;
+            for i in 0..len {
+                divisors.push(divisors[i] * power);
+            }
+        }
+    }
+    divisors.sort_unstable();
+    divisors
+}
+
+/// The divisor function `sigma_k(n)`, the sum of the `k`-th powers of all divisors of `n`.
+///
+/// `sigma(n, 0)` counts the divisors and `sigma(n, 1)` sums them.
+pub fn sigma<T: PrimInt>(n: T, k: u32) -> T {
+    factorize(n)
+        .into_iter()
+        .fold(T::one(), |acc, (p, exponent)| {
+            let pk = p.pow(k);
+            let mut sum = T::one();
+            let mut power = T::one();
+            for _ in 0..exponent {
+                power = power * pk;
+                sum = sum + power;
+            }
+            acc * sum
+        })
+}
+
+/// `sigma(n, 1)` for every `n < limit`, index 0 is 0.
+pub fn divisor_sum_sieve(limit: usize) -> Vec<u64> {
+    let mut sums = vec![0; limit];
+    for d in 1..limit {
+        for n in (d..limit).step_by(d) {
+            sums[n] += d as u64;
+        }
+    }
+    sums
+}
+
+/// Sieve of Eratosthenes, `is_prime[n]` for every `n < limit`.
+pub fn prime_sieve(limit: usize) -> Vec<bool> {
+    let mut is_prime = vec![true; limit];
+    for n in is_prime.iter_mut().take(2) {
+        *n = false;
+    }
+    let mut p = 2;
+    while p * p < limit {
+        if is_prime[p] {
+            for n in (p * p..limit).step_by(p) {
+                is_prime[n] = false;
+            }
+        }
+        p += 1;
+    }
+    is_prime
+}
+
+/// All primes below `limit` in ascending order.
+pub fn primes(limit: usize) -> Vec<usize> {
+    prime_sieve(limit)
+        .into_iter()
+        .enumerate()
+        .filter(|&(_, is_prime)| is_prime)
+        .map(|(p, _)| p)
+        .collect()
+}
+
+/// The greatest common divisor of all numbers, 0 for no numbers.
+pub fn gcd_all<T: Integer>(numbers: impl IntoIterator<Item = T>) -> T {
+    numbers.into_iter().fold(T::zero(), |a, b| a.gcd(&b))
+}
+
+/// The least common multiple of all numbers, 1 for no numbers.
+pub fn lcm_all<T: Integer>(numbers: impl IntoIterator<Item = T>) -> T {
+    numbers.into_iter().fold(T::one(), |a, b| a.lcm(&b))
+}
+
+/// `base^exp mod modulus` by repeated squaring.
+pub fn mod_pow<T: Integer + Clone>(base: T, exp: T, modulus: T) -> T {
+    let two = T::one() + T::one();
+    let mut result = T::one().mod_floor(&modulus);
+    let mut base = base.mod_floor(&modulus);
+    let mut exp = exp;
+    while exp > T::zero() {
+        if exp.is_odd() {
+            result = (result * base.clone()).mod_floor(&modulus);
+        }
+        base = (base.clone() * base).mod_floor(&modulus);
+        exp = exp / two.clone();
+    }
+    result
+}
+
+/// The inverse of `a` modulo `modulus`, if `a` and `modulus` are coprime.
+pub fn mod_inverse<T: Integer + Signed + Clone>(a: T, modulus: T) -> Option<T> {
+    let e = a.mod_floor(&modulus).extended_gcd(&modulus);
+    e.gcd.is_one().then(|| e.x.mod_floor(&modulus))
+}
+
+/// Solves the system `x = r_i mod m_i` with the Chinese remainder theorem.
+///
+/// The moduli do not need to be coprime. Returns the smallest non-negative solution together
+/// with the lcm of all moduli, or `None` if the congruences contradict each other.
+pub fn crt<T: Integer + Signed + Clone>(
+    congruences: impl IntoIterator<Item = (T, T)>,
+) -> Option<(T, T)> {
+    congruences
+        .into_iter()
+        .try_fold((T::zero(), T::one()), |(r1, m1), (r2, m2)| {
+            let e = m1.extended_gcd(&m2);
+            let diff = r2 - r1.clone();
+            if !(diff.clone() % e.gcd.clone()).is_zero() {
+                return None;
+            }
+
+            let m2_reduced = m2 / e.gcd.clone();
+            let k = ((diff / e.gcd) * e.x).mod_floor(&m2_reduced);
+            let m = m1.clone() * m2_reduced;
+            Some(((r1 + m1 * k).mod_floor(&m), m))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_factorize() {
+        assert_eq!(factorize(360u64), vec![(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(factorize(97u32), vec![(97, 1)]);
+        assert!(factorize(1i64).is_empty());
+        assert_eq!(factorize(4_294_967_291u32), vec![(4_294_967_291, 1)]);
+        assert_eq!(
+            factorize(u32::MAX),
+            vec![(3, 1), (5, 1), (17, 1), (257, 1), (65537, 1)]
+        );
+        assert_eq!(divisors(4_294_967_291u32), vec![1, 4_294_967_291]);
+    }
+
+    #[test]
+    fn test_divisors_and_sigma() {
+        assert_eq!(divisors(12u64), vec![1, 2, 3, 4, 6, 12]);
+        assert_eq!(divisors(1u64), vec![1]);
+        assert_eq!(sigma(12u64, 0), 6);
+        assert_eq!(sigma(12u64, 1), 28);
+        assert_eq!(sigma(12u64, 2), 1 + 4 + 9 + 16 + 36 + 144);
+        let sieve = divisor_sum_sieve(100);
+        assert!((1..100u64).all(|n| sieve[n as usize] == sigma(n, 1)));
+    }
+
+    #[test]
+    fn test_primes() {
+        assert_eq!(primes(30), vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+        assert!(primes(2).is_empty());
+    }
+
+    #[test]
+    fn test_gcd_lcm() {
+        assert_eq!(gcd_all([12, 18, 30]), 6);
+        assert_eq!(lcm_all([4u64, 6, 10]), 60);
+        assert_eq!(gcd_all(Vec::<i64>::new()), 0);
+    }
+
+    #[test]
+    fn test_modular() {
+        assert_eq!(mod_pow(3u64, 200, 13), 9);
+        assert_eq!(mod_pow(252533i64, 1, 33554393), 252533);
+        assert_eq!(mod_inverse(3i64, 11), Some(4));
+        assert_eq!(mod_inverse(-3i64, 11), Some(7));
+        assert_eq!(mod_inverse(6i64, 9), None);
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt([(2i64, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt([(1i64, 4), (3, 6)]), Some((9, 12)));
+        assert_eq!(crt([(1i64, 4), (2, 6)]), None);
+        assert_eq!(crt(Vec::<(i64, i64)>::new()), Some((0, 1)));
+    }
+}
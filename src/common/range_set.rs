@@ -0,0 +1,364 @@
+use std::cmp::Ordering;
+use std::ops::{Range, RangeInclusive};
+
+use num::PrimInt;
+
+use crate::common::Vec2i;
+
+/// A set of integers stored as sorted, disjoint and non-adjacent inclusive ranges, so that
+/// `T::MAX` can be a member.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct RangeSet<T> {
+    ranges: Vec<RangeInclusive<T>>,
+}
+
+impl<T: PrimInt> FromIterator<RangeInclusive<T>> for RangeSet<T> {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<T>>>(iter: I) -> Self {
+        let mut ranges: Vec<_> = iter.into_iter().filter(|r| !r.is_empty()).collect();
+        ranges.sort_unstable_by_key(|r| *r.start());
+        let mut merged: Vec<RangeInclusive<T>> = Vec::with_capacity(ranges.len());
+        for r in ranges {
+            match merged.last_mut() {
+                // also merges adjacent ranges, a range ending at T::MAX absorbs everything after it
+                Some(last)
+                    if last
+                        .end()
+                        .checked_add(&T::one())
+                        .is_none_or(|next| *r.start() <= next) =>
+                {
+                    *last = *last.start()..=*last.end().max(r.end());
+                }
+                _ => merged.push(r),
+            }
+        }
+        Self { ranges: merged }
+    }
+}
+
+impl<T: PrimInt> FromIterator<Range<T>> for RangeSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        iter.into_iter()
+            .filter(|r| !r.is_empty())
+            .map(|r| r.start..=r.end - T::one())
+            .collect()
+    }
+}
+
+impl<T: PrimInt> RangeSet<T> {
+    pub fn new() -> Self {
+        Self { ranges: vec![] }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The number of integers in this set, `None` if it does not fit in `T`.
+    pub fn len(&self) -> Option<T> {
+        self.ranges.iter().try_fold(T::zero(), |acc, r| {
+            (*r.end() - *r.start())
+                .checked_add(&T::one())?
+                .checked_add(&acc)
+        })
+    }
+
+    pub fn ranges(&self) -> &[RangeInclusive<T>] {
+        &self.ranges
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        self.ranges
+            .binary_search_by(|r| {
+                if *r.end() < value {
+                    Ordering::Less
+                } else if *r.start() > value {
+                    Ordering::Greater
+                } else {
+                    Ordering::Equal
+                }
+            })
+            .is_ok()
+    }
+
+    pub fn insert(&mut self, range: RangeInclusive<T>) {
+        *self = self.union(&Self::from_iter([range]));
+    }
+
+    pub fn remove(&mut self, range: RangeInclusive<T>) {
+        *self = self.difference(&Self::from_iter([range]));
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        self.ranges.iter().chain(&other.ranges).cloned().collect()
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = vec![];
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (&self.ranges[i], &other.ranges[j]);
+            let start = *a.start().max(b.start());
+            let end = *a.end().min(b.end());
+            if start <= end {
+                ranges.push(start..=end);
+            }
+            if a.end() < b.end() {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        Self { ranges }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut ranges = vec![];
+        let mut j = 0;
+        for r in &self.ranges {
+            // the first value not yet handled, `None` once everything up to T::MAX is
+            let mut start = Some(*r.start());
+            while j < other.ranges.len() && *other.ranges[j].end() < *r.start() {
+                j += 1;
+            }
+            let mut k = j;
+            while let Some(s) = start {
+                let Some(o) = other.ranges.get(k).filter(|o| o.start() <= r.end()) else {
+                    break;
+                };
+                if *o.start() > s {
+                    ranges.push(s..=*o.start() - T::one());
+                }
+                if *o.end() >= s {
+                    start = o.end().checked_add(&T::one());
+                }
+                k += 1;
+            }
+            if let Some(s) = start.filter(|s| s <= r.end()) {
+                ranges.push(s..=*r.end());
+            }
+        }
+        Self { ranges }
+    }
+}
+
+/// An axis-aligned rectangle covering `min.x..max.x` and `min.y..max.y`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Rect {
+    pub min: Vec2i,
+    pub max: Vec2i,
+}
+
+impl Rect {
+    pub fn new(min: Vec2i, max: Vec2i) -> Self {
+        Self { min, max }
+    }
+
+    /// The rectangle spanned by two opposite corner cells, both included.
+    pub fn from_corners(a: &Vec2i, b: &Vec2i) -> Self {
+        Self {
+            min: a.inf(b),
+            max: a.sup(b) + Vec2i::new(1, 1),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x >= self.max.x || self.min.y >= self.max.y
+    }
+
+    pub fn area(&self) -> i64 {
+        if self.is_empty() {
+            0
+        } else {
+            (self.max.x - self.min.x) * (self.max.y - self.min.y)
+        }
+    }
+
+    pub fn contains(&self, pos: &Vec2i) -> bool {
+        self.min.x <= pos.x && pos.x < self.max.x && self.min.y <= pos.y && pos.y < self.max.y
+    }
+
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let r = Rect::new(self.min.sup(&other.min), self.max.inf(&other.max));
+        (!r.is_empty()).then_some(r)
+    }
+
+    /// The parts of `self` not covered by `other`, as at most four disjoint rectangles.
+    fn subtract(&self, other: &Rect) -> impl Iterator<Item = Rect> {
+        let pieces = match self.intersection(other) {
+            None => [Some(*self), None, None, None],
+            Some(i) => [
+                Rect::new(self.min, Vec2i::new(self.max.x, i.min.y)),
+                Rect::new(Vec2i::new(self.min.x, i.max.y), self.max),
+                Rect::new(
+                    Vec2i::new(self.min.x, i.min.y),
+                    Vec2i::new(i.min.x, i.max.y),
+                ),
+                Rect::new(
+                    Vec2i::new(i.max.x, i.min.y),
+                    Vec2i::new(self.max.x, i.max.y),
+                ),
+            ]
+            .map(|r| (!r.is_empty()).then_some(r)),
+        };
+        pieces.into_iter().flatten()
+    }
+}
+
+/// A set of cells stored as disjoint rectangles.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RectSet {
+    rects: Vec<Rect>,
+}
+
+impl FromIterator<Rect> for RectSet {
+    fn from_iter<I: IntoIterator<Item = Rect>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for r in iter {
+            set.insert(&r);
+        }
+        set
+    }
+}
+
+impl RectSet {
+    pub fn new() -> Self {
+        Self { rects: vec![] }
+    }
+
+    /// Disjoint rectangles covering exactly the cells of this set.
+    pub fn rects(&self) -> &[Rect] {
+        &self.rects
+    }
+
+    pub fn area(&self) -> i64 {
+        self.rects.iter().map(Rect::area).sum()
+    }
+
+    pub fn contains(&self, pos: &Vec2i) -> bool {
+        self.rects.iter().any(|r| r.contains(pos))
+    }
+
+    fn without(rects: Vec<Rect>, other: &Rect) -> Vec<Rect> {
+        rects.into_iter().flat_map(|r| r.subtract(other)).collect()
+    }
+
+    /// The parts of `rect` not yet in this set.
+    fn uncovered(&self, rect: &Rect) -> Vec<Rect> {
+        self.rects.iter().fold(vec![*rect], Self::without)
+    }
+
+    pub fn insert(&mut self, rect: &Rect) {
+        let uncovered = self.uncovered(rect);
+        self.rects.extend(uncovered);
+    }
+
+    pub fn remove(&mut self, rect: &Rect) {
+        self.rects = Self::without(std::mem::take(&mut self.rects), rect);
+    }
+
+    /// Adds the cells of `rect` not in this set and removes those that are.
+    pub fn toggle(&mut self, rect: &Rect) {
+        let uncovered = self.uncovered(rect);
+        self.remove(rect);
+        self.rects.extend(uncovered);
+    }
+}
+
+/// The area covered by the union of `rects`, using a sweep over the distinct x coordinates.
+pub fn union_area<'a>(rects: impl IntoIterator<Item = &'a Rect>) -> i64 {
+    let rects: Vec<_> = rects.into_iter().filter(|r| !r.is_empty()).collect();
+    let mut xs: Vec<_> = rects.iter().flat_map(|r| [r.min.x, r.max.x]).collect();
+    xs.sort_unstable();
+    xs.dedup();
+    xs.windows(2)
+        .map(|w| {
+            let covered: RangeSet<i64> = rects
+                .iter()
+                .filter(|r| r.min.x <= w[0] && w[1] <= r.max.x)
+                .map(|r| r.min.y..r.max.y)
+                .collect();
+            (w[1] - w[0]) * covered.len().unwrap()
+        })
+        .sum()
+}
+
+#[cfg(test)]
+#[allow(clippy::single_range_in_vec_init)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_range_set_merge() {
+        let set: RangeSet<i64> = [5..8, 0..2, 2..3, 7..10, 12..12].into_iter().collect();
+        assert_eq!(set.ranges(), &[0..=2, 5..=9]);
+        assert_eq!(set.len(), Some(8));
+        assert!(set.contains(9));
+        assert!(!set.contains(3));
+        let set: RangeSet<u32> = [1..=3, 4..=4].into_iter().collect();
+        assert_eq!(set.ranges(), &[1..=4]);
+    }
+
+    #[test]
+    fn test_range_set_ops() {
+        let a: RangeSet<i64> = [0..10, 20..30].into_iter().collect();
+        let b: RangeSet<i64> = [5..25, 28..29].into_iter().collect();
+        assert_eq!(a.union(&b).ranges(), &[0..=29]);
+        assert_eq!(a.intersection(&b).ranges(), &[5..=9, 20..=24, 28..=28]);
+        assert_eq!(a.difference(&b).ranges(), &[0..=4, 25..=27, 29..=29]);
+        assert_eq!(b.difference(&a).ranges(), &[10..=19]);
+        let mut c = a.clone();
+        c.remove(2..=21);
+        c.insert(40..=40);
+        assert_eq!(c.ranges(), &[0..=1, 22..=29, 40..=40]);
+    }
+
+    #[test]
+    fn test_rect_set() {
+        let a = Rect::from_corners(&Vec2i::new(0, 0), &Vec2i::new(3, 3));
+        let b = Rect::from_corners(&Vec2i::new(2, 2), &Vec2i::new(5, 5));
+        let mut set: RectSet = [a, b].into_iter().collect();
+        assert_eq!(set.area(), 16 + 16 - 4);
+        assert_eq!(union_area(&[a, b]), 28);
+        set.toggle(&Rect::from_corners(&Vec2i::new(0, 0), &Vec2i::new(5, 0)));
+        assert_eq!(set.area(), 28 - 4 + 2);
+        assert!(!set.contains(&Vec2i::new(1, 0)));
+        assert!(set.contains(&Vec2i::new(5, 0)));
+        set.remove(&Rect::from_corners(&Vec2i::new(0, 0), &Vec2i::new(9, 9)));
+        assert_eq!(set.area(), 0);
+    }
+
+    #[test]
+    fn test_range_set_extremes() {
+        let set: RangeSet<u32> = [0..=u32::MAX, 3..=5].into_iter().collect();
+        assert_eq!(set.ranges(), &[0..=u32::MAX]);
+        assert!(set.contains(u32::MAX));
+        assert_eq!(set.len(), None);
+
+        let top: RangeSet<u8> = [250..=255, 240..=249].into_iter().collect();
+        assert_eq!(top.ranges(), &[240..=255]);
+        assert!(top.contains(255));
+        assert_eq!(top.len(), Some(16));
+        let low: RangeSet<u8> = [0..=9].into_iter().collect();
+        assert_eq!(top.union(&low).ranges(), &[0..=9, 240..=255]);
+        assert_eq!(
+            top.intersection(&[255..=255].into_iter().collect())
+                .ranges(),
+            &[255..=255]
+        );
+        let all: RangeSet<u8> = [0..=255].into_iter().collect();
+        assert_eq!(all.difference(&top).ranges(), &[0..=239]);
+        assert_eq!(all.difference(&low).ranges(), &[10..=255]);
+        assert!(top.difference(&all).is_empty());
+        let mut gaps = all.clone();
+        gaps.remove(100..=199);
+        gaps.remove(255..=255);
+        assert_eq!(gaps.ranges(), &[0..=99, 200..=254]);
+        assert_eq!(gaps.len(), Some(155));
+
+        let halves: RangeSet<i8> = [-128..=-1, 1..=127].into_iter().collect();
+        assert_eq!(halves.len(), None);
+        assert!(!halves.contains(0));
+    }
+}
@@ -1,33 +1,163 @@
+use std::collections::BTreeMap;
+use std::io::{self, Read};
+use std::ops::RangeInclusive;
+
 use aoc_runner_derive::aoc;
+use rayon::prelude::*;
+
+/// The floors Santa visits, as `(position, floor)` pairs.
+///
+/// The position is the 1-based index of the instruction that was just followed, bytes other than
+/// `(` and `)` do not move Santa and are skipped.
+#[derive(Debug, Clone)]
+pub struct FloorTrace<'a> {
+    instructions: std::iter::Enumerate<std::slice::Iter<'a, u8>>,
+    floor: i64,
+}
+
+pub fn floor_trace(input: &[u8]) -> FloorTrace<'_> {
+    FloorTrace {
+        instructions: input.iter().enumerate(),
+        floor: 0,
+    }
+}
+
+impl Iterator for FloorTrace<'_> {
+    type Item = (usize, i64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (i, &e) in self.instructions.by_ref() {
+            match e {
+                b'(' => self.floor += 1,
+                b')' => self.floor -= 1,
+                _ => continue,
+            }
+            return Some((i + 1, self.floor));
+        }
+        None
+    }
+}
+
+impl FloorTrace<'_> {
+    /// The position at which Santa first enters `floor`, `Some(0)` for the starting floor.
+    pub fn first_reaching(mut self, floor: i64) -> Option<usize> {
+        if self.floor == floor {
+            return Some(0);
+        }
+        self.find(|&(_, f)| f == floor).map(|(i, _)| i)
+    }
+
+    /// The lowest and highest floor visited, including the starting floor.
+    pub fn floor_range(self) -> RangeInclusive<i64> {
+        let start = self.floor;
+        let (min, max) = self.fold((start, start), |(min, max), (_, f)| {
+            (min.min(f), max.max(f))
+        });
+        min..=max
+    }
+
+    /// How many instructions Santa spends on each floor, the starting floor counts once before
+    /// the first instruction.
+    pub fn histogram(self) -> BTreeMap<i64, usize> {
+        let mut histogram = BTreeMap::from([(self.floor, 1)]);
+        for (_, f) in self {
+            *histogram.entry(f).or_default() += 1;
+        }
+        histogram
+    }
+}
+
+/// Bytes handled per task, large enough to amortize scheduling and small enough to balance.
+const CHUNK_SIZE: usize = 1 << 20;
+
+/// The floor change of a chunk, written branch-free so it vectorizes.
+fn chunk_delta(chunk: &[u8]) -> i64 {
+    chunk
+        .iter()
+        .map(|&b| (b == b'(') as i64 - (b == b')') as i64)
+        .sum()
+}
+
+/// The floor change of a chunk and the lowest floor reached inside it, both relative to the floor
+/// at the start of the chunk.
+fn chunk_summary(chunk: &[u8]) -> (i64, i64) {
+    chunk.iter().fold((0, 0), |(floor, min), &b| {
+        let floor = floor + (b == b'(') as i64 - (b == b')') as i64;
+        (floor, min.min(floor))
+    })
+}
+
+/// The final floor, counting chunks in parallel.
+pub fn final_floor(input: &[u8]) -> i64 {
+    input.par_chunks(CHUNK_SIZE).map(chunk_delta).sum()
+}
+
+/// The position of the first instruction that takes Santa below `floor`.
+///
+/// The chunks are summarized in parallel, a prefix scan over the summaries then finds the first
+/// chunk that dips below `floor` and only that chunk is traced.
+pub fn first_below(input: &[u8], floor: i64) -> Option<usize> {
+    let summaries: Vec<_> = input.par_chunks(CHUNK_SIZE).map(chunk_summary).collect();
+    let mut start = 0;
+    for (i, (delta, min)) in summaries.into_iter().enumerate() {
+        if start + min < floor {
+            let offset = i * CHUNK_SIZE;
+            let chunk = &input[offset..(offset + CHUNK_SIZE).min(input.len())];
+            let (position, _) = FloorTrace {
+                instructions: chunk.iter().enumerate(),
+                floor: start,
+            }
+            .find(|&(_, f)| f < floor)?;
+            return Some(offset + position);
+        }
+        start += delta;
+    }
+    None
+}
+
+/// [`final_floor`] for input that does not fit into memory.
+pub fn final_floor_from_reader(mut reader: impl Read) -> io::Result<i64> {
+    let mut buf = vec![0; CHUNK_SIZE];
+    let mut floor = 0;
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => return Ok(floor),
+            Ok(n) => floor += chunk_delta(&buf[..n]),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// [`first_below`] for input that does not fit into memory, stops reading once it is found.
+pub fn first_below_from_reader(mut reader: impl Read, floor: i64) -> io::Result<Option<usize>> {
+    let mut buf = vec![0; CHUNK_SIZE];
+    let mut offset = 0;
+    let mut start = 0;
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => return Ok(None),
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        let (delta, min) = chunk_summary(&buf[..n]);
+        if start + min < floor {
+            return Ok(first_below(&buf[..n], floor - start).map(|position| offset + position));
+        }
+        start += delta;
+        offset += n;
+    }
+}
 
 #[aoc(day1, part1)]
 pub fn part1(input: &[u8]) -> i64 {
-    input.iter().fold(0i64, |a, &e| match e {
-        b'(' => a + 1,
-        b')' => a - 1,
-        _ => unreachable!(),
-    })
+    final_floor(input)
 }
 
 #[aoc(day1, part2)]
-pub fn part2(input: &[u8]) -> usize {
-    input
-        .iter()
-        .enumerate()
-        .try_fold(0i64, |a, (i, &e)| {
-            Ok(match e {
-                b'(' => a + 1,
-                b')' => {
-                    let a = a - 1;
-                    if a < 0 {
-                        return Err(i + 1);
-                    }
-                    a
-                }
-                _ => unreachable!(),
-            })
-        })
-        .unwrap_err()
+pub fn part2(input: &[u8]) -> Option<usize> {
+    first_below(input, 0)
 }
 
 #[cfg(test)]
@@ -83,11 +213,59 @@ mod tests {
 
     #[test]
     fn test_part2_1() {
-        assert_eq!(part2(b")"), 1);
+        assert_eq!(part2(b")"), Some(1));
     }
 
     #[test]
     fn test_part2_2() {
-        assert_eq!(part2(b"()())"), 5);
+        assert_eq!(part2(b"()())"), Some(5));
+    }
+
+    #[test]
+    fn test_part2_never_basement() {
+        assert_eq!(part2(b"(()"), None);
+        assert_eq!(part2(b""), None);
+    }
+
+    #[test]
+    fn test_trailing_newline() {
+        assert_eq!(part1(b"(()\n"), 1);
+        assert_eq!(part2(b"())\r\n"), Some(3));
+    }
+
+    #[test]
+    fn test_chunked() {
+        // the basement is first entered in the third chunk
+        let mut input = vec![b'('; CHUNK_SIZE];
+        input.extend(vec![b')'; 2 * CHUNK_SIZE + 10]);
+        input.push(b'\n');
+        assert_eq!(part1(&input), -(CHUNK_SIZE as i64) - 10);
+        assert_eq!(part2(&input), Some(2 * CHUNK_SIZE + 1));
+        assert_eq!(part2(&input), floor_trace(&input).first_reaching(-1));
+        assert_eq!(
+            final_floor_from_reader(input.as_slice()).unwrap(),
+            part1(&input)
+        );
+        assert_eq!(
+            first_below_from_reader(input.as_slice(), 0).unwrap(),
+            part2(&input)
+        );
+        assert_eq!(first_below_from_reader(&b"(()"[..], 0).unwrap(), None);
+    }
+
+    #[test]
+    fn test_floor_trace() {
+        assert_eq!(
+            floor_trace(b"(()\n)").collect::<Vec<_>>(),
+            vec![(1, 1), (2, 2), (3, 1), (5, 0)]
+        );
+        assert_eq!(floor_trace(b"(()").first_reaching(2), Some(2));
+        assert_eq!(floor_trace(b"(()").first_reaching(0), Some(0));
+        assert_eq!(floor_trace(b"(()").first_reaching(3), None);
+        assert_eq!(floor_trace(b"))(((((").floor_range(), -2..=3);
+        assert_eq!(
+            floor_trace(b"()(").histogram(),
+            BTreeMap::from([(0, 2), (1, 2)])
+        );
     }
 }
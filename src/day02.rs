@@ -1,15 +1,22 @@
+use std::cmp::Reverse;
+use std::f64::consts::PI;
+use std::fmt::Write;
+use std::ops::Range;
 use std::str::FromStr;
 
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
+use num::Num;
+use serde::Serialize;
+use thiserror::Error;
 
 use crate::common::parse_lines;
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct Cuboid {
-    l: u64,
-    w: u64,
-    h: u64,
+    pub l: u64,
+    pub w: u64,
+    pub h: u64,
 }
 
 impl FromStr for Cuboid {
@@ -25,7 +32,24 @@ impl FromStr for Cuboid {
     }
 }
 
-impl Cuboid {
+/// A gift shape measured in feet, `Unit` is the number type of the measurements.
+pub trait Shape {
+    type Unit: Num + Copy;
+
+    fn surface(&self) -> Self::Unit;
+
+    fn volume(&self) -> Self::Unit;
+
+    /// The area of the smallest face.
+    fn smallest_face(&self) -> Self::Unit;
+
+    /// The length of the shortest ribbon loop around the shape.
+    fn smallest_perimeter(&self) -> Self::Unit;
+}
+
+impl Shape for Cuboid {
+    type Unit = u64;
+
     fn surface(&self) -> u64 {
         2 * (self.l * self.w + self.w * self.h + self.h * self.l)
     }
@@ -34,12 +58,409 @@ impl Cuboid {
         self.l * self.w * self.h
     }
 
-    fn wrapping_paper(&self) -> u64 {
-        self.surface() + (self.l * self.w).min(self.w * self.h).min(self.h * self.l)
+    fn smallest_face(&self) -> u64 {
+        (self.l * self.w).min(self.w * self.h).min(self.h * self.l)
+    }
+
+    fn smallest_perimeter(&self) -> u64 {
+        2 * (self.l + self.w).min(self.l + self.h).min(self.w + self.h)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Cylinder {
+    pub radius: f64,
+    pub height: f64,
+}
+
+impl Shape for Cylinder {
+    type Unit = f64;
+
+    fn surface(&self) -> f64 {
+        2.0 * PI * self.radius * (self.radius + self.height)
+    }
+
+    fn volume(&self) -> f64 {
+        PI * self.radius * self.radius * self.height
+    }
+
+    fn smallest_face(&self) -> f64 {
+        PI * self.radius * self.radius
+    }
+
+    /// Either around the side or lengthwise over both caps.
+    fn smallest_perimeter(&self) -> f64 {
+        (2.0 * PI * self.radius).min(2.0 * (2.0 * self.radius + self.height))
+    }
+}
+
+/// How much paper and ribbon a shape needs, the provided methods implement the puzzle's rules.
+pub trait WrappingPolicy<S: Shape + ?Sized> {
+    /// The extra paper on top of the surface.
+    fn slack(&self, shape: &S) -> S::Unit {
+        shape.smallest_face()
+    }
+
+    fn paper(&self, shape: &S) -> S::Unit {
+        shape.surface() + self.slack(shape)
+    }
+
+    /// The ribbon around the shape.
+    fn wrap(&self, shape: &S) -> S::Unit {
+        shape.smallest_perimeter()
+    }
+
+    /// The ribbon for the bow.
+    fn bow(&self, shape: &S) -> S::Unit {
+        shape.volume()
+    }
+
+    fn ribbon(&self, shape: &S) -> S::Unit {
+        self.wrap(shape) + self.bow(shape)
+    }
+}
+
+/// The rules of the puzzle.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct PuzzlePolicy;
+
+impl<S: Shape + ?Sized> WrappingPolicy<S> for PuzzlePolicy {}
+
+/// The itemised values of a single box of an order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BoxReport {
+    pub index: usize,
+    pub length: u64,
+    pub width: u64,
+    pub height: u64,
+    pub surface: u64,
+    pub slack: u64,
+    pub paper: u64,
+    pub ribbon: u64,
+    pub bow: u64,
+    pub total_ribbon: u64,
+}
+
+impl BoxReport {
+    const CSV_HEADER: &'static str =
+        "index,length,width,height,surface,slack,paper,ribbon,bow,total_ribbon";
+
+    pub fn new(index: usize, cuboid: &Cuboid, policy: &impl WrappingPolicy<Cuboid>) -> Self {
+        Self {
+            index,
+            length: cuboid.l,
+            width: cuboid.w,
+            height: cuboid.h,
+            surface: cuboid.surface(),
+            slack: policy.slack(cuboid),
+            paper: policy.paper(cuboid),
+            ribbon: policy.wrap(cuboid),
+            bow: policy.bow(cuboid),
+            total_ribbon: policy.ribbon(cuboid),
+        }
+    }
+}
+
+/// Total, extremes and mean of one value over all boxes, the extremes and mean are `None`
+/// for an empty order.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Stats {
+    pub total: u64,
+    pub min: Option<u64>,
+    pub max: Option<u64>,
+    pub mean: Option<f64>,
+}
+
+impl Stats {
+    fn new(values: impl Iterator<Item = u64> + Clone) -> Self {
+        let total: u64 = values.clone().sum();
+        let count = values.clone().count();
+        Self {
+            total,
+            min: values.clone().min(),
+            max: values.max(),
+            mean: (count > 0).then(|| total as f64 / count as f64),
+        }
+    }
+}
+
+/// Every box of an order together with statistics over all of them.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OrderReport {
+    pub boxes: Vec<BoxReport>,
+    pub paper: Stats,
+    pub ribbon: Stats,
+}
+
+impl OrderReport {
+    pub fn new(cuboids: &[Cuboid]) -> Self {
+        Self::with_policy(cuboids, &PuzzlePolicy)
+    }
+
+    pub fn with_policy(cuboids: &[Cuboid], policy: &impl WrappingPolicy<Cuboid>) -> Self {
+        let boxes: Vec<_> = cuboids
+            .iter()
+            .enumerate()
+            .map(|(i, c)| BoxReport::new(i, c, policy))
+            .collect();
+        let paper = Stats::new(boxes.iter().map(|b| b.paper));
+        let ribbon = Stats::new(boxes.iter().map(|b| b.total_ribbon));
+        Self {
+            boxes,
+            paper,
+            ribbon,
+        }
+    }
+
+    /// One line per box with a header line, the statistics are left out.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(BoxReport::CSV_HEADER);
+        csv.push('\n');
+        for b in &self.boxes {
+            writeln!(
+                csv,
+                "{},{},{},{},{},{},{},{},{},{}",
+                b.index,
+                b.length,
+                b.width,
+                b.height,
+                b.surface,
+                b.slack,
+                b.paper,
+                b.ribbon,
+                b.bow,
+                b.total_ribbon
+            )
+            .unwrap();
+        }
+        csv
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+/// A rectangular piece of paper needed for one box.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Sheet {
+    /// The index of the box in the order.
+    pub cuboid: usize,
+    pub width: u64,
+    pub length: u64,
+}
+
+/// The pieces for every box: one per face plus a copy of the smallest face as slack, together
+/// they are exactly the box's wrapping paper.
+pub fn sheets(cuboids: &[Cuboid]) -> Vec<Sheet> {
+    cuboids
+        .iter()
+        .enumerate()
+        .flat_map(|(i, c)| {
+            let faces = [(c.l, c.w), (c.w, c.h), (c.h, c.l)];
+            let slack = faces.into_iter().min_by_key(|&(a, b)| a * b).unwrap();
+            faces
+                .into_iter()
+                .flat_map(|f| [f, f])
+                .chain([slack])
+                .map(move |(width, length)| Sheet {
+                    cuboid: i,
+                    width,
+                    length,
+                })
+        })
+        .collect()
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Roll {
+    pub width: u64,
+    pub length: u64,
+}
+
+/// Where a sheet is cut from, `x` runs across the roll and `y` along it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Placement {
+    pub sheet: Sheet,
+    pub roll: usize,
+    pub x: u64,
+    pub y: u64,
+    /// Whether the sheet's width runs along the roll.
+    pub rotated: bool,
+}
+
+impl Placement {
+    /// The extent across the roll.
+    pub fn size_x(&self) -> u64 {
+        if self.rotated {
+            self.sheet.length
+        } else {
+            self.sheet.width
+        }
+    }
+
+    /// The extent along the roll.
+    pub fn size_y(&self) -> u64 {
+        if self.rotated {
+            self.sheet.width
+        } else {
+            self.sheet.length
+        }
+    }
+}
+
+/// A straight cut through the paper, in the order the cuts have to be made.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cut {
+    /// Across the roll at distance `y` from its start.
+    Across { roll: usize, y: u64, x: Range<u64> },
+    /// Along the roll at distance `x` from its edge.
+    Along { roll: usize, x: u64, y: Range<u64> },
+}
+
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PlanError {
+    #[error("sheet {0:?} does not fit onto a roll")]
+    SheetTooLarge(Sheet),
+}
+
+/// A guillotine layout of sheets on rolls, every cut goes all the way through the piece of
+/// paper it is made in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CuttingPlan {
+    pub roll: Roll,
+    pub placements: Vec<Placement>,
+    pub cuts: Vec<Cut>,
+    /// The length taken from each roll, up to the last cut across it.
+    pub used_lengths: Vec<u64>,
+}
+
+struct Shelf {
+    roll: usize,
+    y: u64,
+    height: u64,
+    used_width: u64,
+    placements: Vec<usize>,
+}
+
+impl CuttingPlan {
+    /// Packs the sheets with first-fit decreasing height: sheets are laid out side by side on
+    /// shelves spanning the width of a roll, a shelf is separated from the next one by a cut
+    /// across the roll.
+    pub fn new(roll: Roll, sheets: &[Sheet]) -> Result<Self, PlanError> {
+        let mut oriented = sheets
+            .iter()
+            .map(|&sheet| {
+                let (long, short) = (sheet.width.max(sheet.length), sheet.width.min(sheet.length));
+                // put the long side across the roll when possible to keep shelves low
+                let (size_x, size_y) = if long <= roll.width {
+                    (long, short)
+                } else {
+                    (short, long)
+                };
+                if size_x > roll.width || size_y > roll.length {
+                    return Err(PlanError::SheetTooLarge(sheet));
+                }
+                Ok((sheet, size_x, size_y))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        oriented.sort_by_key(|&(_, size_x, size_y)| (Reverse(size_y), Reverse(size_x)));
+
+        let mut placements = vec![];
+        let mut shelves: Vec<Shelf> = vec![];
+        let mut used_lengths: Vec<u64> = vec![];
+        for (sheet, size_x, size_y) in oriented {
+            let shelf = match shelves
+                .iter()
+                .position(|s| s.used_width + size_x <= roll.width && size_y <= s.height)
+            {
+                Some(i) => i,
+                None => {
+                    let roll_index = used_lengths
+                        .iter()
+                        .position(|&l| l + size_y <= roll.length)
+                        .unwrap_or_else(|| {
+                            used_lengths.push(0);
+                            used_lengths.len() - 1
+                        });
+                    shelves.push(Shelf {
+                        roll: roll_index,
+                        y: used_lengths[roll_index],
+                        height: size_y,
+                        used_width: 0,
+                        placements: vec![],
+                    });
+                    used_lengths[roll_index] += size_y;
+                    shelves.len() - 1
+                }
+            };
+
+            let s = &mut shelves[shelf];
+            s.placements.push(placements.len());
+            placements.push(Placement {
+                sheet,
+                roll: s.roll,
+                x: s.used_width,
+                y: s.y,
+                rotated: size_x != sheet.width,
+            });
+            s.used_width += size_x;
+        }
+
+        shelves.sort_by_key(|s| (s.roll, s.y));
+        let mut cuts = vec![];
+        for s in &shelves {
+            let end = s.y + s.height;
+            if end < roll.length {
+                cuts.push(Cut::Across {
+                    roll: s.roll,
+                    y: end,
+                    x: 0..roll.width,
+                });
+            }
+            for p in s.placements.iter().map(|&i| &placements[i]) {
+                let right = p.x + p.size_x();
+                if right < roll.width {
+                    cuts.push(Cut::Along {
+                        roll: s.roll,
+                        x: right,
+                        y: s.y..end,
+                    });
+                }
+                if p.y + p.size_y() < end {
+                    cuts.push(Cut::Across {
+                        roll: s.roll,
+                        y: p.y + p.size_y(),
+                        x: p.x..right,
+                    });
+                }
+            }
+        }
+
+        Ok(Self {
+            roll,
+            placements,
+            cuts,
+            used_lengths,
+        })
+    }
+
+    pub fn rolls_used(&self) -> usize {
+        self.used_lengths.len()
     }
 
-    fn ribbon(&self) -> u64 {
-        2 * (self.l + self.w).min(self.l + self.h).min(self.w + self.h) + self.volume()
+    /// The share of the paper taken from the rolls that ends up in no sheet, in percent.
+    pub fn waste_percentage(&self) -> f64 {
+        let used: u64 = self.used_lengths.iter().map(|l| l * self.roll.width).sum();
+        if used == 0 {
+            return 0.0;
+        }
+        let needed: u64 = self
+            .placements
+            .iter()
+            .map(|p| p.sheet.width * p.sheet.length)
+            .sum();
+        100.0 * (used - needed) as f64 / used as f64
     }
 }
 
@@ -50,12 +471,12 @@ pub fn input_generator(input: &str) -> Vec<Cuboid> {
 
 #[aoc(day2, part1)]
 pub fn part1(input: &[Cuboid]) -> u64 {
-    input.iter().map(Cuboid::wrapping_paper).sum()
+    input.iter().map(|c| PuzzlePolicy.paper(c)).sum()
 }
 
 #[aoc(day2, part2)]
 pub fn part2(input: &[Cuboid]) -> u64 {
-    input.iter().map(Cuboid::ribbon).sum()
+    input.iter().map(|c| PuzzlePolicy.ribbon(c)).sum()
 }
 
 #[cfg(test)]
@@ -83,4 +504,174 @@ mod tests {
     fn test_part2_2() {
         assert_eq!(part2(&input_generator("1x1x10")), 14);
     }
+
+    #[test]
+    fn test_order_report() {
+        let report = OrderReport::new(&input_generator("2x3x4\n1x1x10\n"));
+        assert_eq!(
+            report.boxes[0],
+            BoxReport {
+                index: 0,
+                length: 2,
+                width: 3,
+                height: 4,
+                surface: 52,
+                slack: 6,
+                paper: 58,
+                ribbon: 10,
+                bow: 24,
+                total_ribbon: 34,
+            }
+        );
+        assert_eq!(
+            report.paper,
+            Stats {
+                total: 101,
+                min: Some(43),
+                max: Some(58),
+                mean: Some(50.5),
+            }
+        );
+        assert_eq!(report.ribbon.total, 48);
+        assert_eq!(
+            report.to_csv(),
+            "index,length,width,height,surface,slack,paper,ribbon,bow,total_ribbon\n\
+             0,2,3,4,52,6,58,10,24,34\n\
+             1,1,1,10,42,1,43,4,10,14\n"
+        );
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(json["boxes"][1]["paper"], 43);
+        assert_eq!(json["ribbon"]["mean"], 24.0);
+    }
+
+    #[test]
+    fn test_empty_order_report() {
+        let report = OrderReport::new(&[]);
+        assert_eq!(report.paper.total, 0);
+        assert_eq!(report.paper.mean, None);
+        assert_eq!(report.to_csv().lines().count(), 1);
+    }
+
+    #[test]
+    fn test_puzzle_policy() {
+        let cuboid = Cuboid { l: 2, w: 3, h: 4 };
+        assert_eq!(PuzzlePolicy.slack(&cuboid), 6);
+        assert_eq!(PuzzlePolicy.paper(&cuboid), 58);
+        assert_eq!(PuzzlePolicy.wrap(&cuboid), 10);
+        assert_eq!(PuzzlePolicy.ribbon(&cuboid), 34);
+
+        let can = Cylinder {
+            radius: 1.0,
+            height: 10.0,
+        };
+        assert_eq!(PuzzlePolicy.paper(&can), 23.0 * PI);
+        assert_eq!(PuzzlePolicy.ribbon(&can), 12.0 * PI);
+        let disc = Cylinder {
+            radius: 10.0,
+            height: 1.0,
+        };
+        assert_eq!(disc.smallest_perimeter(), 42.0);
+    }
+
+    #[test]
+    fn test_custom_policy() {
+        struct NoSlackDoubleBow;
+
+        impl<S: Shape> WrappingPolicy<S> for NoSlackDoubleBow {
+            fn slack(&self, _: &S) -> S::Unit {
+                num::zero()
+            }
+
+            fn bow(&self, shape: &S) -> S::Unit {
+                shape.volume() + shape.volume()
+            }
+        }
+
+        let cuboid = Cuboid { l: 2, w: 3, h: 4 };
+        assert_eq!(NoSlackDoubleBow.paper(&cuboid), 52);
+        assert_eq!(NoSlackDoubleBow.ribbon(&cuboid), 58);
+        let can = Cylinder {
+            radius: 1.0,
+            height: 10.0,
+        };
+        assert_eq!(NoSlackDoubleBow.paper(&can), 22.0 * PI);
+
+        let report = OrderReport::with_policy(&[cuboid], &NoSlackDoubleBow);
+        assert_eq!(report.boxes[0].slack, 0);
+        assert_eq!(report.boxes[0].bow, 48);
+        assert_eq!(report.paper.total, 52);
+        assert_eq!(report.ribbon.total, 58);
+    }
+
+    #[test]
+    fn test_sheets() {
+        let cuboids = input_generator("2x3x4\n1x1x10\n");
+        let sheets = sheets(&cuboids);
+        assert_eq!(sheets.len(), 14);
+        assert_eq!(
+            sheets.iter().map(|s| s.width * s.length).sum::<u64>(),
+            part1(&cuboids)
+        );
+    }
+
+    #[test]
+    fn test_cutting_plan() {
+        let cuboids = input_generator("2x3x4\n1x1x10\n");
+        let roll = Roll {
+            width: 10,
+            length: 12,
+        };
+        let plan = CuttingPlan::new(roll, &sheets(&cuboids)).unwrap();
+        assert_eq!(plan.placements.len(), 14);
+        assert_eq!(plan.rolls_used(), 1);
+        assert_eq!(plan.used_lengths, vec![11]);
+        assert_eq!(plan.waste_percentage(), 100.0 * 9.0 / 110.0);
+
+        for (i, a) in plan.placements.iter().enumerate() {
+            assert!(a.x + a.size_x() <= roll.width && a.y + a.size_y() <= roll.length);
+            assert_eq!(a.size_x() * a.size_y(), a.sheet.width * a.sheet.length);
+            for b in &plan.placements[i + 1..] {
+                let disjoint = a.roll != b.roll
+                    || a.x + a.size_x() <= b.x
+                    || b.x + b.size_x() <= a.x
+                    || a.y + a.size_y() <= b.y
+                    || b.y + b.size_y() <= a.y;
+                assert!(disjoint, "{a:?} overlaps {b:?}");
+            }
+        }
+        let short_rolls = Roll {
+            width: 10,
+            length: 4,
+        };
+        let plan_short = CuttingPlan::new(short_rolls, &sheets(&cuboids)).unwrap();
+        assert_eq!(plan_short.used_lengths, vec![4, 4, 3]);
+        assert_eq!(
+            plan.cuts[0],
+            Cut::Across {
+                roll: 0,
+                y: 3,
+                x: 0..10
+            }
+        );
+    }
+
+    #[test]
+    fn test_cutting_plan_errors() {
+        let roll = Roll {
+            width: 3,
+            length: 5,
+        };
+        let sheet = Sheet {
+            cuboid: 0,
+            width: 4,
+            length: 4,
+        };
+        assert_eq!(
+            CuttingPlan::new(roll, &[sheet]),
+            Err(PlanError::SheetTooLarge(sheet))
+        );
+        let plan = CuttingPlan::new(roll, &[]).unwrap();
+        assert_eq!(plan.rolls_used(), 0);
+        assert_eq!(plan.waste_percentage(), 0.0);
+    }
 }
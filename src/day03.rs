@@ -1,39 +1,379 @@
+use std::iter;
+use std::num::NonZeroUsize;
+use std::str::FromStr;
+
 use aoc_runner_derive::{aoc, aoc_generator};
-use rustc_hash::FxHashSet;
+use rustc_hash::{FxHashMap, FxHashSet};
+use thiserror::Error;
 
-use crate::common::{Direction, Vec2i};
+use crate::common::{Direction, Direction8, Grid, Rect, Vec2i};
 
 #[aoc_generator(day3)]
-pub fn input_generator(input: &str) -> Vec<Direction> {
-    input
-        .trim()
-        .chars()
-        .map(TryInto::try_into)
-        .collect::<Result<_, _>>()
-        .unwrap()
+pub fn input_generator(input: &str) -> Vec<Direction8> {
+    parse_moves(input.trim(), ParseOptions::default()).unwrap()
+}
+
+/// The extensions of the puzzle's `^v<>` alphabet that [`parse_moves`] accepts.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// `↗`, `↘`, `↙` and `↖` move diagonally.
+    pub diagonals: bool,
+    /// A decimal count before a move repeats it, e.g. `3^`.
+    pub run_lengths: bool,
+    /// Whitespace is skipped and `#` starts a comment that runs to the end of the line.
+    pub comments: bool,
+    /// The most moves the input may expand to, guards against huge run lengths.
+    pub max_moves: usize,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            diagonals: false,
+            run_lengths: false,
+            comments: false,
+            max_moves: Self::DEFAULT_MAX_MOVES,
+        }
+    }
+}
+
+impl ParseOptions {
+    pub const DEFAULT_MAX_MOVES: usize = 1 << 24;
+
+    /// Every extension enabled.
+    pub fn extended() -> Self {
+        Self {
+            diagonals: true,
+            run_lengths: true,
+            comments: true,
+            ..Self::default()
+        }
+    }
+}
+
+/// Why [`parse_moves`] failed, positions are byte offsets.
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ParseMovesError {
+    #[error("{found:?} at {position} is not a move")]
+    UnknownMove { position: usize, found: char },
+    #[error("the count at {position} is not followed by a move")]
+    MissingMove { position: usize },
+    #[error("the digit at {position} makes the count too large")]
+    CountOverflow { position: usize },
+    #[error("the moves up to {position} expand to more than {limit} moves")]
+    TooManyMoves { position: usize, limit: usize },
+}
+
+fn parse_move(c: char, diagonals: bool) -> Option<Direction8> {
+    match c {
+        '↗' if diagonals => Some(Direction8::NorthEast),
+        '↘' if diagonals => Some(Direction8::SouthEast),
+        '↙' if diagonals => Some(Direction8::SouthWest),
+        '↖' if diagonals => Some(Direction8::NorthWest),
+        _ => Direction::try_from(c).ok().map(Into::into),
+    }
+}
+
+/// Parses a stream of moves, with the default options only the four directions are allowed.
+pub fn parse_moves(input: &str, options: ParseOptions) -> Result<Vec<Direction8>, ParseMovesError> {
+    let mut moves = vec![];
+    let mut chars = input.char_indices().peekable();
+    while let Some((position, c)) = chars.next() {
+        if options.comments && c == '#' {
+            chars.by_ref().find(|&(_, c)| c == '\n');
+            continue;
+        }
+        if options.comments && c.is_whitespace() {
+            continue;
+        }
+
+        let (count, (move_position, c)) = if options.run_lengths && c.is_ascii_digit() {
+            let mut count = c.to_digit(10).unwrap() as usize;
+            while let Some((digit_position, digit)) = chars.next_if(|(_, c)| c.is_ascii_digit()) {
+                count = count
+                    .checked_mul(10)
+                    .and_then(|n| n.checked_add(digit.to_digit(10).unwrap() as usize))
+                    .ok_or(ParseMovesError::CountOverflow {
+                        position: digit_position,
+                    })?;
+            }
+            (
+                count,
+                chars
+                    .next()
+                    .ok_or(ParseMovesError::MissingMove { position })?,
+            )
+        } else {
+            (1, (position, c))
+        };
+        let direction = parse_move(c, options.diagonals).ok_or(ParseMovesError::UnknownMove {
+            position: move_position,
+            found: c,
+        })?;
+        if count > options.max_moves - moves.len() {
+            return Err(ParseMovesError::TooManyMoves {
+                position: move_position,
+                limit: options.max_moves,
+            });
+        }
+        moves.extend(iter::repeat_n(direction, count));
+    }
+    Ok(moves)
+}
+
+/// Decides which agent follows each move.
+pub trait MoveAssignment {
+    /// The agent in `0..agents` that follows move number `step`.
+    fn agent(&self, step: usize, agents: usize) -> usize;
+}
+
+/// The agents take turns move by move, as Santa and Robo-Santa do.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct RoundRobin;
+
+impl MoveAssignment for RoundRobin {
+    fn agent(&self, step: usize, agents: usize) -> usize {
+        step % agents
+    }
+}
+
+/// The agents take turns with blocks of consecutive moves.
+#[derive(Debug, Copy, Clone)]
+pub struct Blocks(pub NonZeroUsize);
+
+impl MoveAssignment for Blocks {
+    fn agent(&self, step: usize, agents: usize) -> usize {
+        (step / self.0) % agents
+    }
+}
+
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ParseAssignmentError {
+    #[error("empty assignment")]
+    Empty,
+    #[error("{found:?} at {position} is not an agent number")]
+    InvalidAgent { position: usize, found: char },
+}
+
+/// An explicit agent for every move given as a string of base 36 digits, e.g. `"0012"`. The
+/// string is repeated if there are more moves than digits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explicit(Vec<usize>);
+
+impl FromStr for Explicit {
+    type Err = ParseAssignmentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let agents = s
+            .char_indices()
+            .map(|(position, c)| {
+                c.to_digit(36)
+                    .map(|d| d as usize)
+                    .ok_or(ParseAssignmentError::InvalidAgent { position, found: c })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if agents.is_empty() {
+            return Err(ParseAssignmentError::Empty);
+        }
+        Ok(Self(agents))
+    }
+}
+
+impl Explicit {
+    /// The highest agent number, a delivery needs more agents than that.
+    pub fn max_agent(&self) -> usize {
+        self.0.iter().copied().max().unwrap()
+    }
+}
+
+impl MoveAssignment for Explicit {
+    fn agent(&self, step: usize, _agents: usize) -> usize {
+        self.0[step % self.0.len()]
+    }
+}
+
+/// The houses each agent delivered presents to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delivery {
+    visited: Vec<FxHashSet<Vec2i>>,
+}
+
+impl Delivery {
+    pub fn agent_houses(&self, agent: usize) -> &FxHashSet<Vec2i> {
+        &self.visited[agent]
+    }
+
+    pub fn houses_per_agent(&self) -> Vec<usize> {
+        self.visited.iter().map(FxHashSet::len).collect()
+    }
+
+    /// The number of agents that visited each house.
+    fn agents_per_house(&self) -> FxHashMap<Vec2i, usize> {
+        let mut agents = FxHashMap::default();
+        for &house in self.visited.iter().flatten() {
+            *agents.entry(house).or_default() += 1;
+        }
+        agents
+    }
+
+    /// The number of houses visited by more than one agent.
+    pub fn shared_houses(&self) -> usize {
+        self.agents_per_house().values().filter(|&&n| n > 1).count()
+    }
+
+    /// The number of houses visited by any agent.
+    pub fn total_houses(&self) -> usize {
+        self.agents_per_house().len()
+    }
+}
+
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DeliveryError {
+    #[error("at least one agent is needed")]
+    NoAgents,
+    #[error("move {step} is assigned to agent {agent}, but there are only {agents} agents")]
+    UnknownAgent {
+        step: usize,
+        agent: usize,
+        agents: usize,
+    },
+}
+
+/// Lets `agents` agents start at the origin and follow the moves handed out by `assignment`,
+/// `visit` is called with the move number, the agent and the house it moved to.
+fn deliver<'a>(
+    directions: impl IntoIterator<Item = &'a Direction8>,
+    agents: usize,
+    assignment: &impl MoveAssignment,
+    mut visit: impl FnMut(usize, usize, Vec2i),
+) -> Result<(), DeliveryError> {
+    if agents == 0 {
+        return Err(DeliveryError::NoAgents);
+    }
+    let mut current = vec![Vec2i::zeros(); agents];
+    for (step, d) in directions.into_iter().enumerate() {
+        let agent = assignment.agent(step, agents);
+        if agent >= agents {
+            return Err(DeliveryError::UnknownAgent {
+                step,
+                agent,
+                agents,
+            });
+        }
+        current[agent] = d.offset(&current[agent]);
+        visit(step, agent, current[agent]);
+    }
+    Ok(())
+}
+
+/// The houses visited when `agents` agents follow the moves handed out by `assignment`.
+pub fn visited_houses<'a>(
+    directions: impl IntoIterator<Item = &'a Direction8>,
+    agents: usize,
+    assignment: &impl MoveAssignment,
+) -> Result<Delivery, DeliveryError> {
+    let mut visited = vec![FxHashSet::from_iter([Vec2i::zeros()]); agents];
+    deliver(directions, agents, assignment, |_, agent, house| {
+        visited[agent].insert(house);
+    })?;
+    Ok(Delivery { visited })
 }
 
-fn visited_houses<'a>(directions: impl IntoIterator<Item = &'a Direction>) -> FxHashSet<Vec2i> {
-    let mut current = Vec2i::zeros();
-    let mut visited = FxHashSet::from_iter([current]);
-    for d in directions {
-        current = d.offset(&current);
-        visited.insert(current);
+/// A delivery recorded move by move, step 0 is the start with every agent at the origin and
+/// step `i` is the state after the `i`-th move.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    houses: Vec<Vec2i>,
+    first_visits: FxHashMap<Vec2i, usize>,
+    visit_counts: FxHashMap<Vec2i, u32>,
+    /// The inclusive corners of the visited area and the number of distinct houses after each step.
+    progress: Vec<(Vec2i, Vec2i, usize)>,
+}
+
+impl Replay {
+    pub fn new<'a>(
+        directions: impl IntoIterator<Item = &'a Direction8>,
+        agents: usize,
+        assignment: &impl MoveAssignment,
+    ) -> Result<Self, DeliveryError> {
+        let origin = Vec2i::zeros();
+        let mut replay = Self {
+            houses: vec![origin],
+            first_visits: FxHashMap::from_iter([(origin, 0)]),
+            visit_counts: FxHashMap::from_iter([(origin, agents as u32)]),
+            progress: vec![(origin, origin, 1)],
+        };
+        deliver(directions, agents, assignment, |i, _, house| {
+            let step = i + 1;
+            replay.houses.push(house);
+            replay.first_visits.entry(house).or_insert(step);
+            *replay.visit_counts.entry(house).or_default() += 1;
+            let &(min, max, _) = replay.progress.last().unwrap();
+            replay
+                .progress
+                .push((min.inf(&house), max.sup(&house), replay.first_visits.len()));
+        })?;
+        Ok(replay)
+    }
+
+    /// The number of moves.
+    pub fn steps(&self) -> usize {
+        self.houses.len() - 1
     }
 
-    visited
+    /// The house a present was delivered to in `step`, the origin for step 0.
+    pub fn house_at(&self, step: usize) -> Option<Vec2i> {
+        self.houses.get(step).copied()
+    }
+
+    pub fn first_visit(&self, house: &Vec2i) -> Option<usize> {
+        self.first_visits.get(house).copied()
+    }
+
+    /// The number of presents delivered to `house`, the origin gets one per agent.
+    pub fn visit_count(&self, house: &Vec2i) -> u32 {
+        self.visit_counts.get(house).copied().unwrap_or(0)
+    }
+
+    pub fn visit_counts(&self) -> &FxHashMap<Vec2i, u32> {
+        &self.visit_counts
+    }
+
+    /// The number of distinct houses visited up to and including `step`.
+    pub fn houses_until(&self, step: usize) -> usize {
+        self.progress[step.min(self.steps())].2
+    }
+
+    /// The smallest rectangle containing every house visited up to and including `step`.
+    pub fn bounding_box_at(&self, step: usize) -> Rect {
+        let (min, max, _) = self.progress[step.min(self.steps())];
+        Rect::from_corners(&min, &max)
+    }
+
+    /// The visit counts of the whole bounding box, cell `(0, 0)` is the returned position.
+    pub fn heatmap(&self) -> (Grid<u32>, Vec2i) {
+        let bounds = self.bounding_box_at(self.steps());
+        let size = bounds.max - bounds.min;
+        let mut grid = Grid::new_from_element(size.x as usize, size.y as usize, 0);
+        for (house, &count) in &self.visit_counts {
+            grid[house - bounds.min] = count;
+        }
+        (grid, bounds.min)
+    }
 }
 
 #[aoc(day3, part1)]
-pub fn part1(input: &[Direction]) -> usize {
-    visited_houses(input).len()
+pub fn part1(input: &[Direction8]) -> usize {
+    visited_houses(input, 1, &RoundRobin)
+        .unwrap()
+        .total_houses()
 }
 
 #[aoc(day3, part2)]
-pub fn part2(input: &[Direction]) -> usize {
-    let mut visited = visited_houses(input.iter().step_by(2));
-    visited.extend(visited_houses(input.iter().skip(1).step_by(2)));
-    visited.len()
+pub fn part2(input: &[Direction8]) -> usize {
+    visited_houses(input, 2, &RoundRobin)
+        .unwrap()
+        .total_houses()
 }
 
 #[cfg(test)]
@@ -71,4 +411,193 @@ mod tests {
     fn test_part2_3() {
         assert_eq!(part2(&input_generator("^v^v^v^v^v")), 11);
     }
+
+    #[test]
+    fn test_agents() {
+        let input = input_generator("^>v<^^");
+        let delivery = visited_houses(&input, 3, &RoundRobin).unwrap();
+        assert_eq!(delivery.houses_per_agent(), vec![3, 3, 2]);
+        assert_eq!(delivery.shared_houses(), 1);
+        assert_eq!(delivery.total_houses(), 6);
+
+        let delivery = visited_houses(&input, 2, &Blocks(NonZeroUsize::new(2).unwrap())).unwrap();
+        assert_eq!(delivery.houses_per_agent(), vec![5, 3]);
+        assert_eq!(
+            delivery.agent_houses(1),
+            &FxHashSet::from_iter([Vec2i::zeros(), Vec2i::new(0, 1), Vec2i::new(-1, 1)])
+        );
+
+        let explicit: Explicit = "001".parse().unwrap();
+        assert_eq!(
+            visited_houses(&input, 2, &explicit)
+                .unwrap()
+                .houses_per_agent(),
+            vec![4, 2]
+        );
+    }
+
+    #[test]
+    fn test_explicit_assignment_errors() {
+        assert_eq!("".parse::<Explicit>(), Err(ParseAssignmentError::Empty));
+        assert_eq!(
+            "01-".parse::<Explicit>(),
+            Err(ParseAssignmentError::InvalidAgent {
+                position: 2,
+                found: '-'
+            })
+        );
+
+        let input = input_generator("^>v<");
+        let explicit: Explicit = "012".parse().unwrap();
+        assert_eq!(explicit.max_agent(), 2);
+        assert_eq!(
+            visited_houses(&input, 2, &explicit),
+            Err(DeliveryError::UnknownAgent {
+                step: 2,
+                agent: 2,
+                agents: 2
+            })
+        );
+        assert_eq!(
+            visited_houses(&input, 0, &RoundRobin),
+            Err(DeliveryError::NoAgents)
+        );
+        assert_eq!(
+            Replay::new(&input, 2, &explicit).unwrap_err(),
+            DeliveryError::UnknownAgent {
+                step: 2,
+                agent: 2,
+                agents: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_replay() {
+        let input = input_generator("^>v<^^");
+        let replay = Replay::new(&input, 1, &RoundRobin).unwrap();
+        assert_eq!(replay.steps(), 6);
+        assert_eq!(replay.house_at(2), Some(Vec2i::new(1, -1)));
+        assert_eq!(replay.first_visit(&Vec2i::zeros()), Some(0));
+        assert_eq!(replay.first_visit(&Vec2i::new(0, -1)), Some(1));
+        assert_eq!(replay.first_visit(&Vec2i::new(0, -2)), Some(6));
+        assert_eq!(replay.first_visit(&Vec2i::new(5, 5)), None);
+        assert_eq!(replay.visit_count(&Vec2i::zeros()), 2);
+        assert_eq!(replay.visit_count(&Vec2i::new(0, -1)), 2);
+        assert_eq!(replay.houses_until(4), 4);
+        assert_eq!(replay.houses_until(100), 5);
+        assert_eq!(
+            replay.bounding_box_at(1),
+            Rect::from_corners(&Vec2i::new(0, -1), &Vec2i::zeros())
+        );
+        assert_eq!(replay.bounding_box_at(6).area(), 6);
+
+        let (heatmap, offset) = replay.heatmap();
+        assert_eq!((heatmap.size_x, heatmap.size_y), (2, 3));
+        assert_eq!(offset, Vec2i::new(0, -2));
+        assert_eq!(
+            heatmap.iter().copied().collect::<Vec<_>>(),
+            vec![1, 0, 2, 1, 2, 1]
+        );
+        assert_eq!(heatmap.iter().sum::<u32>(), replay.steps() as u32 + 1);
+    }
+
+    #[test]
+    fn test_replay_agents() {
+        let input = input_generator("^v^v^v^v^v");
+        let replay = Replay::new(&input, 2, &RoundRobin).unwrap();
+        assert_eq!(replay.visit_count(&Vec2i::zeros()), 2);
+        assert_eq!(replay.houses_until(replay.steps()), part2(&input));
+        assert_eq!(replay.bounding_box_at(10).area(), 11);
+    }
+
+    #[test]
+    fn test_parse_moves() {
+        let options = ParseOptions::extended();
+        assert_eq!(
+            parse_moves("^>", ParseOptions::default()),
+            Ok(vec![Direction8::North, Direction8::East])
+        );
+        assert_eq!(
+            parse_moves("2^ ↗ # up twice, then diagonally\n\t0v<", options),
+            Ok(vec![
+                Direction8::North,
+                Direction8::North,
+                Direction8::NorthEast,
+                Direction8::West
+            ])
+        );
+        assert_eq!(parse_moves("12>", options).unwrap().len(), 12);
+        assert_eq!(parse_moves("#^\n", options), Ok(vec![]));
+        assert_eq!(input_generator(" ^>\n").len(), 2);
+    }
+
+    #[test]
+    fn test_parse_moves_errors() {
+        assert_eq!(
+            parse_moves("^^ v", ParseOptions::default()),
+            Err(ParseMovesError::UnknownMove {
+                position: 2,
+                found: ' '
+            })
+        );
+        assert_eq!(
+            parse_moves("^↗", ParseOptions::default()),
+            Err(ParseMovesError::UnknownMove {
+                position: 1,
+                found: '↗'
+            })
+        );
+        assert_eq!(
+            parse_moves("^3^", ParseOptions::default()),
+            Err(ParseMovesError::UnknownMove {
+                position: 1,
+                found: '3'
+            })
+        );
+        let options = ParseOptions::extended();
+        assert_eq!(
+            parse_moves("^ 3x", options),
+            Err(ParseMovesError::UnknownMove {
+                position: 3,
+                found: 'x'
+            })
+        );
+        assert_eq!(
+            parse_moves("v 42", options),
+            Err(ParseMovesError::MissingMove { position: 2 })
+        );
+        assert_eq!(
+            parse_moves("<99999999999999999999999>", options),
+            Err(ParseMovesError::CountOverflow { position: 20 })
+        );
+        assert_eq!(
+            parse_moves("<99999999999999999>", options),
+            Err(ParseMovesError::TooManyMoves {
+                position: 18,
+                limit: ParseOptions::DEFAULT_MAX_MOVES
+            })
+        );
+        let options = ParseOptions {
+            max_moves: 5,
+            ..options
+        };
+        assert_eq!(parse_moves("2^ 3v", options).unwrap().len(), 5);
+        assert_eq!(
+            parse_moves("2^ 3v <", options),
+            Err(ParseMovesError::TooManyMoves {
+                position: 6,
+                limit: 5
+            })
+        );
+    }
+
+    #[test]
+    fn test_diagonal_delivery() {
+        let input = parse_moves("4↗ 4↙", ParseOptions::extended()).unwrap();
+        assert_eq!(part1(&input), 5);
+        let replay = Replay::new(&input, 1, &RoundRobin).unwrap();
+        assert_eq!(replay.bounding_box_at(4).area(), 25);
+        assert_eq!(replay.visit_count(&Vec2i::zeros()), 2);
+    }
 }
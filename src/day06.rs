@@ -4,7 +4,7 @@ use std::str::FromStr;
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 
-use crate::common::{parse_lines, parse_vec, Vec2i};
+use crate::common::{parse_lines, parse_vec, BitGrid, Vec2i};
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum Action {
@@ -82,30 +82,6 @@ trait Light {
     fn value(&self) -> <Self as Light>::Value;
 }
 
-impl Light for bool {
-    type Value = usize;
-
-    fn off() -> Self {
-        false
-    }
-
-    fn turn_on(&mut self) {
-        *self = true;
-    }
-
-    fn turn_off(&mut self) {
-        *self = false;
-    }
-
-    fn toggle(&mut self) {
-        *self = !*self;
-    }
-
-    fn value(&self) -> <Self as Light>::Value {
-        *self as _
-    }
-}
-
 impl Light for u64 {
     type Value = u64;
 
@@ -148,6 +124,19 @@ where
     grid.iter().map(Light::value).sum()
 }
 
+fn simulate_bits<const SX: usize, const SY: usize>(instructions: &[Instruction]) -> usize {
+    let mut grid = BitGrid::new(SX, SY);
+    for i in instructions {
+        match i.action {
+            Action::TurnOn => grid.set_rect(&i.from, &i.to),
+            Action::TurnOff => grid.clear_rect(&i.from, &i.to),
+            Action::Toggle => grid.toggle_rect(&i.from, &i.to),
+        }
+    }
+
+    grid.count_ones()
+}
+
 #[aoc_generator(day6)]
 pub fn input_generator(input: &str) -> Vec<Instruction> {
     parse_lines(input).unwrap()
@@ -155,7 +144,7 @@ pub fn input_generator(input: &str) -> Vec<Instruction> {
 
 #[aoc(day6, part1)]
 pub fn part1(input: &[Instruction]) -> usize {
-    simulate::<bool, _, 1000, 1000>(input)
+    simulate_bits::<1000, 1000>(input)
 }
 
 #[aoc(day6, part2)]
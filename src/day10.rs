@@ -20,7 +20,7 @@ fn look_and_say(it: impl IntoIterator<Item = u8>) -> impl Iterator<Item = u8> {
 fn apply_look_and_say(n: usize, input: &[u8]) -> Vec<u8> {
     let mut result: Vec<_> = look_and_say(input.iter().copied()).collect();
     for _ in 1..n {
-        result = look_and_say(result.into_iter()).collect();
+        result = look_and_say(result).collect();
     }
     result
 }
@@ -41,17 +41,11 @@ pub fn part2(input: &Value) -> i64 {
     let mut result = 0;
     for_each_df(input, |v| {
         match v {
-            Value::Object(o) => {
-                if o.values().any(|v| {
-                    if let Value::String(s) = v {
-                        if s == "red" {
-                            return true;
-                        }
-                    }
-                    false
-                }) {
-                    return false;
-                }
+            Value::Object(o)
+                if o.values()
+                    .any(|v| matches!(v, Value::String(s) if s == "red")) =>
+            {
+                return false;
             }
             Value::Number(n) => {
                 result += n.as_i64().unwrap();
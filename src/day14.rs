@@ -1,34 +1,23 @@
 use crate::common::parse_lines;
+use aoc2015_derive::FromTemplate;
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
-use std::str::FromStr;
 
 #[aoc_generator(day14)]
 pub fn input_generator(input: &str) -> Vec<Reindeer> {
     parse_lines(input).unwrap()
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, FromTemplate)]
+#[template(
+    "{_} can fly {speed} km/s for {speed_time} seconds, but then must rest for {rest_time} seconds."
+)]
 pub struct Reindeer {
     pub speed: u64,
     pub speed_time: u64,
     pub rest_time: u64,
 }
 
-impl FromStr for Reindeer {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (speed, _, _, speed_time, _, _, _, _, _, _, rest_time, _) =
-            s.split_whitespace().skip(3).collect_tuple().ok_or(())?;
-        Ok(Self {
-            speed: speed.parse().map_err(|_| ())?,
-            speed_time: speed_time.parse().map_err(|_| ())?,
-            rest_time: rest_time.parse().map_err(|_| ())?,
-        })
-    }
-}
-
 impl Reindeer {
     fn distance_travelled(&self, time: u64) -> u64 {
         let cycle_time = self.speed_time + self.rest_time;
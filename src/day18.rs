@@ -2,6 +2,10 @@ use crate::common::{Grid, Vec2i};
 use aoc_runner_derive::{aoc, aoc_generator};
 
 #[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde-serialize",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub enum Tile {
     #[default]
     Off,
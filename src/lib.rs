@@ -1,9 +1,9 @@
 use aoc_runner_derive::aoc_lib;
 
 pub mod common;
-mod day01;
-mod day02;
-mod day03;
+pub mod day01;
+pub mod day02;
+pub mod day03;
 mod day04;
 mod day05;
 mod day06;